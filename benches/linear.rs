@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use not_empty::NonEmptySlice;
+
+use alg_quickselect::{get_pivot::RandomPivot, introselect, quickselect_linear};
+
+const LEN: usize = 1_000_000;
+
+fn random(len: usize, seed: u64) -> Vec<u64> {
+    let mut random = RandomPivot::new(seed);
+    (0..len).map(|_| random.next_u64()).collect()
+}
+
+fn bench_select(c: &mut Criterion, group: &str, data: &[u64]) {
+    let mut group = c.benchmark_group(group);
+    group.sample_size(20);
+    group.bench_with_input(BenchmarkId::new("linear", data.len()), data, |b, data| {
+        b.iter_batched_ref(
+            || data.to_vec(),
+            |v| {
+                let s = NonEmptySlice::new_mut(v).unwrap();
+                quickselect_linear(s, LEN / 2);
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_with_input(
+        BenchmarkId::new("introselect", data.len()),
+        data,
+        |b, data| {
+            b.iter_batched_ref(
+                || data.to_vec(),
+                |v| {
+                    let s = NonEmptySlice::new_mut(v).unwrap();
+                    introselect(s, LEN / 2);
+                },
+                BatchSize::LargeInput,
+            )
+        },
+    );
+    group.finish();
+}
+
+fn integers(c: &mut Criterion) {
+    bench_select(c, "u64", &random(LEN, 1));
+}
+
+fn sorted(c: &mut Criterion) {
+    let data: Vec<_> = (0..LEN as u64).collect();
+    bench_select(c, "u64_sorted", &data);
+}
+
+criterion_group!(benches, integers, sorted);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use not_empty::NonEmptySlice;
+
+use alg_quickselect::{
+    get_pivot::{median_of_three, RandomPivot},
+    partition::{Block, Hoare, Lomuto, PartitionScheme, ThreeWay},
+    quickselect_with_scheme,
+};
+
+const LEN: usize = 100_000;
+
+/// A 64-byte record ordered lexicographically, to measure the cost of moving larger elements.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Record([u64; 8]);
+
+fn random(len: usize, seed: u64) -> Vec<u64> {
+    let mut random = RandomPivot::new(seed);
+    (0..len).map(|_| random.next_u64()).collect()
+}
+
+fn bench_schemes<T: Ord + Clone>(c: &mut Criterion, group: &str, data: &[T]) {
+    fn bench<T: Ord + Clone>(
+        group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>,
+        name: &str,
+        data: &[T],
+        scheme: impl PartitionScheme + Copy,
+    ) {
+        group.bench_with_input(BenchmarkId::new(name, data.len()), data, |b, data| {
+            b.iter_batched_ref(
+                || data.to_vec(),
+                |v| {
+                    let s = NonEmptySlice::new_mut(v).unwrap();
+                    quickselect_with_scheme(s, LEN / 2, median_of_three, scheme);
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    let mut group = c.benchmark_group(group);
+    bench(&mut group, "lomuto", data, Lomuto);
+    bench(&mut group, "three_way", data, ThreeWay);
+    bench(&mut group, "hoare", data, Hoare);
+    bench(&mut group, "block", data, Block);
+    group.finish();
+}
+
+fn integers(c: &mut Criterion) {
+    bench_schemes(c, "u64", &random(LEN, 1));
+}
+
+fn duplicates(c: &mut Criterion) {
+    let data: Vec<_> = random(LEN, 3).into_iter().map(|x| x % 16).collect();
+    bench_schemes(c, "u64_16_values", &data);
+}
+
+fn records(c: &mut Criterion) {
+    let data: Vec<_> = random(LEN, 2).into_iter().map(|x| Record([x; 8])).collect();
+    bench_schemes(c, "record", &data);
+}
+
+criterion_group!(benches, integers, duplicates, records);
+criterion_main!(benches);
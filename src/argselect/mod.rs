@@ -0,0 +1,149 @@
+//! Selection of the index of the k-th smallest element, without reordering the input.
+
+use core::cmp::Ordering;
+
+use not_empty::NonEmptySlice;
+
+use crate::introselect_by;
+
+/// Finds the index of the k-th smallest element of a slice, without reordering the slice.
+///
+/// The selection is done over the indices of `s`, written into `indices`, so `s` can be shared
+/// or kept in its original order. After the call, `indices` is partitioned like
+/// [`quickselect`](crate::quickselect) would have partitioned `s`: the index of every element
+/// that sorts before the k-th smallest element comes before `indices[k]`, and the returned value
+/// is `indices[k]`.
+///
+/// Equal elements are ordered by index, so the result is the index the k-th smallest element
+/// would come from in a stable sort of `s`.
+///
+/// This works under `no_std`.
+#[cfg_attr(
+    feature = "std",
+    doc = "See [`argselect`] for a form that allocates the buffer."
+)]
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for the slice `s`,
+/// or if `indices` does not have the same length as `s`.
+///
+/// # Examples
+///
+/// ```
+/// use alg_quickselect::argselect_in;
+///
+/// let scores = [72, 95, 88, 61, 95];
+/// let mut indices = [0; 5];
+///
+/// // The best score, ties broken by index
+/// assert_eq!(argselect_in(&scores, 3, &mut indices), 1);
+/// assert_eq!(argselect_in(&scores, 4, &mut indices), 4);
+/// ```
+pub fn argselect_in<T: Ord>(s: &[T], k: usize, indices: &mut [usize]) -> usize {
+    argselect_by_in(s, k, T::cmp, indices)
+}
+
+/// [`argselect_in`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`argselect_in`].
+pub fn argselect_by_in<T>(
+    s: &[T],
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    indices: &mut [usize],
+) -> usize {
+    if k >= s.len() {
+        panic!(
+            "index out of bounds: the len is {len} but the index is {idx}",
+            len = s.len(),
+            idx = k
+        );
+    }
+    if indices.len() != s.len() {
+        panic!(
+            "index buffer length mismatch: the len is {len} but the buffer len is {buf}",
+            len = s.len(),
+            buf = indices.len()
+        );
+    }
+
+    for (i, index) in indices.iter_mut().enumerate() {
+        *index = i;
+    }
+    // Safety: k < s.len() == indices.len()
+    let indices = unsafe { NonEmptySlice::new_mut_unchecked(indices) };
+    *introselect_by(indices, k, |&a, &b| compare(&s[a], &s[b]).then(a.cmp(&b)))
+}
+
+/// [`argselect_in`] that allocates the index buffer.
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for the slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use alg_quickselect::argselect;
+///
+/// let words = ["pear", "apple", "fig", "cherry"];
+///
+/// let median = argselect(&words, 1);
+/// assert_eq!((median, words[median]), (3, "cherry"));
+/// ```
+#[cfg(feature = "std")]
+pub fn argselect<T: Ord>(s: &[T], k: usize) -> usize {
+    argselect_by(s, k, T::cmp)
+}
+
+/// [`argselect`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`argselect`].
+#[cfg(feature = "std")]
+pub fn argselect_by<T>(s: &[T], k: usize, compare: impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut indices = std::vec![0; s.len()];
+    argselect_by_in(s, k, compare, &mut indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::random;
+
+    #[test]
+    fn matches_stable_sort() {
+        let data = random::<200>(1, 37);
+        let mut sorted: [usize; 200] = core::array::from_fn(|i| i);
+        sorted.sort_by_key(|&i| data[i]);
+
+        let mut indices = [0; 200];
+        for (k, &expected) in sorted.iter().enumerate() {
+            assert_eq!(argselect_in(&data, k, &mut indices), expected);
+        }
+    }
+
+    #[test]
+    fn by_comparator() {
+        let data = [3, 1, 4, 1, 5, 9, 2, 6];
+        let mut indices = [0; 8];
+        assert_eq!(argselect_by_in(&data, 0, |a, b| b.cmp(a), &mut indices), 5);
+        assert_eq!(indices[0], 5);
+    }
+
+    #[test]
+    #[should_panic = "index buffer length mismatch"]
+    fn short_buffer() {
+        argselect_in(&[1, 2, 3], 0, &mut [0; 2]);
+    }
+
+    #[test]
+    #[should_panic = "index out of bounds"]
+    fn empty() {
+        argselect_in::<u32>(&[], 0, &mut []);
+    }
+}
@@ -0,0 +1,96 @@
+//! Slices permuted along with the slice being selected in.
+//!
+//! Columnar data often keeps the keys in one slice and the other fields in parallel slices.
+//! [`quickselect_with_companions`](crate::quickselect_with_companions) selects in the key slice
+//! and replays every swap on the companion slices, so that the rows stay aligned.
+//!
+//! [`Companions`] is implemented for mutable references to slices, arrays and vectors, and for
+//! tuples of companions, so several columns of different types can be passed at once. Columns
+//! passed by value would be swapped as copies and left unchanged, so they are not accepted.
+//!
+//! ```
+//! use not_empty::NonEmptySlice;
+//! use alg_quickselect::{get_pivot::MedianOfThree, quickselect_with_companions};
+//!
+//! let mut ages = [41, 23, 35, 67, 19];
+//! let mut names = ["Ada", "Bo", "Cy", "Di", "Ed"];
+//! let mut ids = [1u32, 2, 3, 4, 5];
+//!
+//! let s = NonEmptySlice::new_mut(&mut ages).unwrap();
+//! let age = *quickselect_with_companions(s, 2, MedianOfThree, (&mut names, &mut ids[..]));
+//!
+//! assert_eq!((age, names[2], ids[2]), (35, "Cy", 3));
+//! ```
+
+/// Slices whose rows are swapped along with the elements of the slice being selected in.
+pub trait Companions {
+    /// Returns `true` if every companion slice has `len` rows.
+    fn has_len(&self, len: usize) -> bool;
+
+    /// Swaps the rows `a` and `b` of every companion slice.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `a` or `b` is out of bounds.
+    fn swap(&mut self, a: usize, b: usize);
+}
+
+impl<U> Companions for &mut [U] {
+    #[inline]
+    fn has_len(&self, len: usize) -> bool {
+        self.len() == len
+    }
+
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        <[U]>::swap(self, a, b);
+    }
+}
+
+impl<U, const N: usize> Companions for &mut [U; N] {
+    #[inline]
+    fn has_len(&self, len: usize) -> bool {
+        N == len
+    }
+
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        <[U]>::swap(*self, a, b);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<U> Companions for &mut std::vec::Vec<U> {
+    #[inline]
+    fn has_len(&self, len: usize) -> bool {
+        self.len() == len
+    }
+
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        <[U]>::swap(self, a, b);
+    }
+}
+
+macro_rules! impl_companions_tuple {
+    ($($name:ident . $index:tt),+) => {
+        impl<$($name: Companions),+> Companions for ($($name,)+) {
+            #[inline]
+            fn has_len(&self, len: usize) -> bool {
+                $(self.$index.has_len(len))&&+
+            }
+
+            #[inline]
+            fn swap(&mut self, a: usize, b: usize) {
+                $(self.$index.swap(a, b);)+
+            }
+        }
+    };
+}
+
+impl_companions_tuple!(A.0);
+impl_companions_tuple!(A.0, B.1);
+impl_companions_tuple!(A.0, B.1, C.2);
+impl_companions_tuple!(A.0, B.1, C.2, D.3);
+impl_companions_tuple!(A.0, B.1, C.2, D.3, E.4);
+impl_companions_tuple!(A.0, B.1, C.2, D.3, E.4, F.5);
@@ -0,0 +1,49 @@
+//! Errors returned by the fallible selection functions.
+
+use core::fmt;
+
+/// Error returned by [`try_quickselect`](crate::try_quickselect) and its variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectError {
+    /// The rank is out of bounds for the slice.
+    RankOutOfBounds {
+        /// Length of the slice.
+        len: usize,
+        /// The requested rank.
+        k: usize,
+    },
+    /// The pivot strategy returned an index out of bounds for the slice passed to it.
+    InvalidPivot {
+        /// Length of the slice passed to the pivot strategy.
+        len: usize,
+        /// The returned index.
+        idx: usize,
+        /// Number of partitions made before the pivot strategy was called.
+        iteration: usize,
+    },
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RankOutOfBounds { len, k } => {
+                write!(
+                    f,
+                    "index out of bounds: the len is {len} but the index is {k}"
+                )
+            }
+            Self::InvalidPivot {
+                len,
+                idx,
+                iteration,
+            } => write!(
+                f,
+                "invalid pivot: index out of bounds: the len is {len} but the index is {idx} \
+                 (iteration {iteration})"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelectError {}
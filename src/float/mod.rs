@@ -0,0 +1,259 @@
+//! Selection over floating-point numbers.
+//!
+//! [`f32`] and [`f64`] do not implement [`Ord`] because of NaN, so they cannot be passed to
+//! [`quickselect`](crate::quickselect) directly. The functions in this module order the numbers
+//! with [`f64::total_cmp`] and make the handling of NaN explicit through a [`NanPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use not_empty::NonEmptySlice;
+//! use alg_quickselect::{float::{quickselect_f64, NanPolicy}, get_pivot::middle_index};
+//!
+//! let mut arr = [2.5, f64::NAN, -1.0, 4.0, 0.5];
+//! let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+//!
+//! // The 2nd smallest number that is not NaN
+//! let result = quickselect_f64(s, 1, NanPolicy::Ignore, middle_index);
+//! assert_eq!(result, Ok(&mut 0.5));
+//! ```
+
+use core::fmt;
+
+use not_empty::NonEmptySlice;
+
+use crate::{get_pivot::PivotStrategy, quantile::Numeric, quickselect_by_with_strategy};
+
+/// What to do when the slice contains NaN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NanPolicy {
+    /// Return a NaN from the slice if there is one, like arithmetic does.
+    ///
+    /// The slice is left unchanged when a NaN is found.
+    Propagate,
+    /// Move every NaN to the end of the slice and select among the remaining numbers.
+    ///
+    /// The rank `k` is then relative to the numbers that are not NaN.
+    Ignore,
+    /// Return [`FloatSelectError::Nan`] if the slice contains NaN.
+    Error,
+}
+
+/// Error returned by the selection functions of this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatSelectError {
+    /// The slice contains NaN under [`NanPolicy::Error`].
+    Nan {
+        /// Index of the first NaN found.
+        index: usize,
+    },
+    /// The rank is out of bounds for the slice, or under [`NanPolicy::Ignore`], for the numbers
+    /// that are not NaN.
+    RankOutOfBounds {
+        /// Length of the slice, or under [`NanPolicy::Ignore`], number of elements that are not
+        /// NaN.
+        len: usize,
+        /// The requested rank.
+        k: usize,
+    },
+}
+
+impl fmt::Display for FloatSelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nan { index } => write!(f, "slice contains NaN at index {index}"),
+            Self::RankOutOfBounds { len, k } => write!(
+                f,
+                "index out of bounds: the len is {len} but the index is {k}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FloatSelectError {}
+
+fn select<F: Numeric>(
+    s: &mut NonEmptySlice<F>,
+    k: usize,
+    nan_policy: NanPolicy,
+    get_pivot: impl PivotStrategy<F>,
+) -> Result<&mut F, FloatSelectError> {
+    let len = s.len().get();
+    if k >= len && nan_policy != NanPolicy::Ignore {
+        return Err(FloatSelectError::RankOutOfBounds { len, k });
+    }
+
+    let s = match nan_policy {
+        NanPolicy::Propagate => match s.iter().position(|x| x.is_nan()) {
+            Some(index) => return Ok(&mut s[index]),
+            None => s,
+        },
+        NanPolicy::Error => match s.iter().position(|x| x.is_nan()) {
+            Some(index) => return Err(FloatSelectError::Nan { index }),
+            None => s,
+        },
+        NanPolicy::Ignore => {
+            let mut len = 0;
+            for j in 0..s.len().get() {
+                if !s[j].is_nan() {
+                    s.swap(len, j);
+                    len += 1;
+                }
+            }
+            if k >= len {
+                return Err(FloatSelectError::RankOutOfBounds { len, k });
+            }
+            // Safety: k < len, so the prefix is not empty
+            unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..len]) }
+        }
+    };
+    Ok(quickselect_by_with_strategy(s, k, F::total_cmp, get_pivot))
+}
+
+/// Finds the k-th smallest [`f32`] in an unsorted, non-empty slice.
+///
+/// Numbers are ordered by [`f32::total_cmp`], so `-0.0` is less than `+0.0`.
+/// NaN is handled according to `nan_policy`.
+///
+/// # Errors
+///
+/// Returns [`FloatSelectError::RankOutOfBounds`] if `k` is out of bounds for the slice `s`,
+/// or under [`NanPolicy::Ignore`], for the numbers of `s` that are not NaN.
+/// See [`NanPolicy`] for the other errors.
+///
+/// # Panics
+///
+/// Panics if the index returned by `get_pivot` is out of bounds
+/// for the input slice passed to it.
+pub fn quickselect_f32(
+    s: &mut NonEmptySlice<f32>,
+    k: usize,
+    nan_policy: NanPolicy,
+    get_pivot: impl PivotStrategy<f32>,
+) -> Result<&mut f32, FloatSelectError> {
+    select(s, k, nan_policy, get_pivot)
+}
+
+/// Finds the k-th smallest [`f64`] in an unsorted, non-empty slice.
+///
+/// Numbers are ordered by [`f64::total_cmp`], so `-0.0` is less than `+0.0`.
+/// NaN is handled according to `nan_policy`.
+///
+/// # Errors
+///
+/// Returns [`FloatSelectError::RankOutOfBounds`] if `k` is out of bounds for the slice `s`,
+/// or under [`NanPolicy::Ignore`], for the numbers of `s` that are not NaN.
+/// See [`NanPolicy`] for the other errors.
+///
+/// # Panics
+///
+/// Panics if the index returned by `get_pivot` is out of bounds
+/// for the input slice passed to it.
+pub fn quickselect_f64(
+    s: &mut NonEmptySlice<f64>,
+    k: usize,
+    nan_policy: NanPolicy,
+    get_pivot: impl PivotStrategy<f64>,
+) -> Result<&mut f64, FloatSelectError> {
+    select(s, k, nan_policy, get_pivot)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Ordering;
+
+    use super::*;
+    use crate::get_pivot::{middle_index, MedianOfMedians, MedianOfThree, Ninther};
+
+    #[test]
+    fn nan_policies() {
+        let data = [3.0, f64::NAN, -0.0, 1.5, f64::NAN, 0.0];
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert!(quickselect_f64(s, 0, NanPolicy::Propagate, middle_index)
+            .unwrap()
+            .is_nan());
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(
+            quickselect_f64(s, 0, NanPolicy::Error, middle_index),
+            Err(FloatSelectError::Nan { index: 1 })
+        );
+
+        for (k, expected) in [-0.0, 0.0, 1.5, 3.0].into_iter().enumerate() {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let x = *quickselect_f64(s, k, NanPolicy::Ignore, middle_index).unwrap();
+            assert_eq!(x.total_cmp(&expected), Ordering::Equal);
+            assert!(arr[4..].iter().all(|x| x.is_nan()));
+        }
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(
+            quickselect_f64(s, 4, NanPolicy::Ignore, middle_index),
+            Err(FloatSelectError::RankOutOfBounds { len: 4, k: 4 })
+        );
+    }
+
+    #[test]
+    fn rank_out_of_bounds() {
+        for policy in [NanPolicy::Propagate, NanPolicy::Error] {
+            let mut arr = [1.0, f64::NAN, 0.5];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(
+                quickselect_f64(s, 3, policy, middle_index),
+                Err(FloatSelectError::RankOutOfBounds { len: 3, k: 3 })
+            );
+        }
+
+        for k in [2, 3, 10] {
+            let mut arr = [1.0f32, f32::NAN, 0.5];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(
+                quickselect_f32(s, k, NanPolicy::Ignore, middle_index),
+                Err(FloatSelectError::RankOutOfBounds { len: 2, k })
+            );
+        }
+    }
+
+    #[test]
+    fn without_nan() {
+        for policy in [NanPolicy::Propagate, NanPolicy::Ignore, NanPolicy::Error] {
+            let mut arr = [2.0f32, -7.5, 1e9, 0.25, -1e-3];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(quickselect_f32(s, 2, policy, middle_index), Ok(&mut 0.25));
+        }
+    }
+
+    #[test]
+    fn strategy_types() {
+        fn check(get_pivot: impl PivotStrategy<f64> + Copy) {
+            // Reversed, with a NaN in the middle
+            let mut data: [f64; 201] = core::array::from_fn(|i| (200 - i) as f64 / 4.0);
+            data[100] = f64::NAN;
+
+            for k in [0, 1, 99, 100, 198, 199] {
+                let mut arr = data;
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                let x = *quickselect_f64(s, k, NanPolicy::Ignore, get_pivot).unwrap();
+                let expected = if k < 100 { k } else { k + 1 } as f64 / 4.0;
+                assert_eq!(x, expected, "k = {k}");
+            }
+
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(
+                quickselect_f64(s, 0, NanPolicy::Error, get_pivot),
+                Err(FloatSelectError::Nan { index: 100 })
+            );
+        }
+
+        check(MedianOfThree);
+        check(Ninther);
+        check(MedianOfMedians);
+    }
+}
@@ -0,0 +1,194 @@
+//! The [Floyd–Rivest algorithm](https://en.wikipedia.org/wiki/Floyd%E2%80%93Rivest_algorithm).
+
+use core::{cmp::Ordering, f64::consts::LN_2};
+
+use not_empty::NonEmptySlice;
+
+use crate::{check_rank, get_pivot::Ninther, introselect_unchecked};
+
+/// Ranges shorter than this are handed over to [`introselect`](crate::introselect).
+const CUTOFF: usize = 600;
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice
+/// using the [Floyd–Rivest algorithm](https://en.wikipedia.org/wiki/Floyd%E2%80%93Rivest_algorithm).
+///
+/// Each round recursively selects within a small sample to find two elements that bracket
+/// the k-th smallest element with high probability, which lets the partition discard most of
+/// the slice at once. This takes fewer comparisons than [`quickselect`](crate::quickselect)
+/// on large slices, especially when `k` is close to either end.
+///
+/// After the call, the slice is partitioned around index `k` like with
+/// [`quickselect`](crate::quickselect).
+///
+/// # Panics
+///
+/// Panics if the specified value of `k` is out of bounds for the given slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::floyd_rivest;
+///
+/// let mut arr: [u32; 10_000] = core::array::from_fn(|i| (i as u32 * 7919) % 10_000);
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(floyd_rivest(s, 9_990), &mut 9_990);
+/// ```
+pub fn floyd_rivest<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut T {
+    floyd_rivest_by(s, k, T::cmp)
+}
+
+/// [`floyd_rivest`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Panics if the specified value of `k` is out of bounds for the given slice `s`.
+pub fn floyd_rivest_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut T {
+    check_rank(s, k);
+    let last = s.len().get() - 1;
+    select(s, 0, last, k, &mut compare);
+    &mut s[k]
+}
+
+/// Partitions `s[left..=right]` around index `k`.
+fn select<T, F>(s: &mut [T], mut left: usize, mut right: usize, k: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    debug_assert!(left <= k && k <= right && right < s.len());
+
+    while right > left {
+        if right - left < CUTOFF {
+            // Safety: left <= right, so the range is not empty, and k is within it
+            let range = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[left..=right]) };
+            unsafe { introselect_unchecked(range, k - left, compare, Ninther) };
+            return;
+        }
+
+        // Select within a sample around k, so that s[k] likely ends up close to the k-th smallest
+        // element of the whole range. The sample size and the distance from k follow the
+        // original algorithm, with integer approximations of the logarithm and roots.
+        let n = right - left + 1;
+        let i = k - left + 1;
+        let z = n.ilog2() as f64 * LN_2;
+        let sample = 0.5 * icbrt(n as u128 * n as u128) as f64;
+        let deviation = 0.5 * ((z * sample * (n as f64 - sample) / n as f64) as u64).isqrt() as f64;
+        let deviation = if 2 * i < n { -deviation } else { deviation };
+        let ratio = sample / n as f64;
+        let new_left = (k as f64 - i as f64 * ratio + deviation).max(left as f64) as usize;
+        let new_right = (k as f64 + (n - i) as f64 * ratio + deviation).min(right as f64) as usize;
+        select(s, new_left, new_right, k, compare);
+
+        // Partition the range around s[k], keeping track of where the pivot is
+        s.swap(left, k);
+        let mut pivot = left;
+        if compare(&s[right], &s[pivot]) == Ordering::Greater {
+            s.swap(left, right);
+            pivot = right;
+        }
+        // Whichever end does not hold the pivot stops the scans below
+        let (mut i, mut j) = (left, right);
+        while i < j {
+            s.swap(i, j);
+            if pivot == i {
+                pivot = j;
+            } else if pivot == j {
+                pivot = i;
+            }
+            i += 1;
+            j -= 1;
+            while compare(&s[i], &s[pivot]) == Ordering::Less {
+                i += 1;
+            }
+            while compare(&s[j], &s[pivot]) == Ordering::Greater {
+                j -= 1;
+            }
+        }
+        if pivot == left {
+            s.swap(left, j);
+        } else {
+            j += 1;
+            s.swap(j, right);
+        }
+
+        // The pivot is now at its final position j
+        match j.cmp(&k) {
+            Ordering::Equal => return,
+            Ordering::Less => left = j + 1,
+            Ordering::Greater => right = j - 1, // j > k >= 0
+        }
+    }
+}
+
+/// Integer cube root, rounded down.
+fn icbrt(x: u128) -> u64 {
+    let (mut low, mut high) = (0u64, 1u64 << 43);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if (mid as u128).pow(3) <= x {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{self, random};
+
+    const LEN: usize = 5000;
+
+    #[test]
+    fn matches_sort() {
+        for (seed, modulo) in [(1, u32::MAX), (2, 1000), (3, 10), (4, 1)] {
+            let data = random::<LEN>(seed, modulo);
+            let mut sorted = data;
+            sorted.sort_unstable();
+
+            for k in [0, 1, 17, LEN / 10, LEN / 2, LEN - 600, LEN - 2, LEN - 1] {
+                let mut arr = data;
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                assert_eq!(*floyd_rivest(s, k), sorted[k], "seed = {seed}, k = {k}");
+                assert!(arr[..k].iter().all(|x| *x <= sorted[k]));
+                assert!(arr[k..].iter().all(|x| *x >= sorted[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn patterns() {
+        for (name, data) in test_data::patterns::<LEN>() {
+            for k in [0, LEN / 3, LEN - 1] {
+                let mut arr = data;
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                assert_eq!(*floyd_rivest(s, k), k as u32, "{name}, k = {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn cube_root() {
+        for x in [
+            0u128,
+            1,
+            7,
+            8,
+            26,
+            27,
+            1_000_000,
+            999_999,
+            u64::MAX as u128 * 1000,
+        ] {
+            let c = icbrt(x) as u128;
+            assert!(c.pow(3) <= x && (c + 1).pow(3) > x, "{x}");
+        }
+    }
+}
@@ -14,18 +14,34 @@
 //! to find the k-th smallest element.
 //!
 //! 1. If possible, the strategy should choose a pivot closest to the *value*
-//! of the k-th smallest element to eliminate the largest amount of potential candidates.
+//!    of the k-th smallest element to eliminate the largest amount of potential candidates.
 //! 2. Avoid doing too much work. Compute spent selecting the pivot is compute not spent
-//! doing more iterations.
+//!    doing more iterations.
 //! 3. If the first goal is not possible, instead aim to select a pivot that
-//! splits the remaining values evenly into two halves.
+//!    splits the remaining values evenly into two halves.
+//!
+//! ## The [`PivotStrategy`] trait
+//!
+//! [`quickselect`](crate::quickselect) and its variants take `get_pivot` as a closure or a
+//! function such as those of this module. A [`PivotStrategy`] also sees the rank being searched
+//! for and the number of previous iterations, and can keep state across calls. Functions that
+//! take one, like [`quickselect_with_strategy`](crate::quickselect_with_strategy), accept these
+//! closures and functions as well.
+//!
+//! The strategies of this module are also available as types, such as [`MedianOfThree`]. These
+//! compare elements with the comparator of the selection, so they work for any element type,
+//! including floats through [`quickselect_f64`](crate::float::quickselect_f64), and their
+//! comparisons and swaps are seen by an [`Observer`](crate::stats::Observer), see
+//! [`PivotStrategy::pivot_by`].
 //!
 //! ## Invariants
 //!
 //! This crate already ensures the following preconditions:
 //! - The slice received by `get_pivot` is never empty (as per the signature).
 //! - For each invocation of `get_pivot` since this function was called,
-//! the given slice will always be shorter by at least 1 element.
+//!   the given slice will always be shorter by at least 1 element than the slice it was split
+//!   from. This is the slice of the previous invocation, except in
+//!   [`multiselect`](crate::multiselect), which can go on to the larger side of an earlier split.
 //!
 //! The user must ensure the following postcondition:
 //! - The output index is valid for the given slice.
@@ -46,8 +62,22 @@
 //! ```
 //!
 //! Another common approach is to use random index. Though this approach is theoretically
-//! as fast or slower than the simple approach. The random strategy is **not implemented**
-//! in this crate due to the extra dependency required.
+//! as fast or slower than the simple approach, it cannot be defeated by a fixed input pattern.
+//! [`RandomPivot`] implements it with a small built-in generator, without extra dependencies.
+//!
+//! ```rust
+//! # use not_empty::NonEmptySlice;
+//! use alg_quickselect::{get_pivot::RandomPivot, quickselect_with_strategy};
+//!
+//! let mut arr = [4, 2, 5, 1, 3];
+//! let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+//!
+//! // A fixed seed gives reproducible selections
+//! let mut random = RandomPivot::new(42);
+//! assert_eq!(quickselect_with_strategy(s, 2, &mut random), &mut 3);
+//! ```
+//!
+//! An external generator such as `rand` can be used through a closure:
 //!
 //! ```rust, ignore
 //! # use not_empty::NonEmptySlice;
@@ -55,18 +85,116 @@
 //!
 //! let mut rng = thread_rng();
 //! let random = |s: &mut NonEmptySlice<_>| rng.gen_range(0..s.len().get());
-//!
-//! // OR
-//!
-//! fn random<T>(s: &mut NonEmptySlice<T>) -> usize {
-//!     thread_rng().gen_range(0..s.len().get())
-//! }
 //! ```
 //!
 // TODO: Add more examples
 
+use core::cmp::Ordering;
+
 use not_empty::NonEmptySlice;
 
+mod random;
+
+pub use random::RandomPivot;
+
+/// A strategy for choosing the pivot of each partitioning step.
+///
+/// See the [module-level documentation](self) for guidelines.
+///
+/// # Examples
+///
+/// A strategy that starts with the middle element,
+/// then moves its guess towards the rank being searched for.
+///
+/// ```
+/// use core::cmp::Ordering;
+///
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::PivotStrategy, quickselect_with_strategy};
+///
+/// struct TowardsRank;
+///
+/// impl<T> PivotStrategy<T> for TowardsRank {
+///     fn pivot_by<F, S>(
+///         &mut self,
+///         s: &mut NonEmptySlice<T>,
+///         k: usize,
+///         iteration: usize,
+///         _compare: &mut F,
+///         _on_swap: &mut S,
+///     ) -> usize
+///     where
+///         F: FnMut(&T, &T) -> Ordering,
+///         S: FnMut(usize, usize),
+///     {
+///         let middle = s.len().get() / 2;
+///         if iteration == 0 { middle } else { (middle + k) / 2 }
+///     }
+/// }
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+/// assert_eq!(quickselect_with_strategy(s, 3, TowardsRank), &mut 4);
+/// ```
+pub trait PivotStrategy<T> {
+    /// Returns the index of the pivot in `s`.
+    ///
+    /// `k` is the rank being searched for, relative to `s`.
+    /// `iteration` is the number of pivots chosen before this one during the current selection.
+    /// `compare` is the comparator of the selection: a strategy that compares elements should
+    /// use it, so that it works for any `T` and its comparisons are seen by an [`Observer`].
+    ///
+    /// Every swap of two elements of `s` MUST be reported to `on_swap` with their indices in `s`,
+    /// in the order they are made, like [`PartitionScheme::partition`] does.
+    ///
+    /// [`PartitionScheme::partition`]: crate::partition::PartitionScheme::partition
+    /// [`Observer`]: crate::stats::Observer
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        k: usize,
+        iteration: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize);
+
+    /// [`pivot_by`](PivotStrategy::pivot_by) with the natural order of `T`, without reporting
+    /// the swaps.
+    #[inline]
+    fn pivot(&mut self, s: &mut NonEmptySlice<T>, k: usize, iteration: usize) -> usize
+    where
+        T: Ord,
+    {
+        self.pivot_by(s, k, iteration, &mut T::cmp, &mut |_, _| {})
+    }
+}
+
+/// Functions and closures only receive the slice: they compare elements on their own,
+/// which requires [`Ord`] for [`median_of_three`] and the like.
+impl<T, P> PivotStrategy<T> for P
+where
+    P: FnMut(&mut NonEmptySlice<T>) -> usize,
+{
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        self(s)
+    }
+}
+
 /// ```rust, ignore
 /// s.len().get() / 2
 /// ```
@@ -88,3 +216,409 @@ pub fn first_index<T>(_: &mut NonEmptySlice<T>) -> usize {
 pub fn last_index<T>(s: &mut NonEmptySlice<T>) -> usize {
     s.len().get() - 1
 }
+
+/// Index of the median of `s[0]`, `s[len / 2]` and `s[len - 1]`.
+///
+/// Unlike the functions above, this does not degrade on sorted
+/// or reverse-sorted input. Organ-pipe input still yields poor pivots, see [`ninther`].
+/// Slices shorter than 3 elements use [`middle_index`].
+#[inline]
+pub fn median_of_three<T: Ord>(s: &mut NonEmptySlice<T>) -> usize {
+    median_of_three_by(s, &mut T::cmp)
+}
+
+/// Index of the median of three medians of three, also known as Tukey's ninther.
+///
+/// The three groups are sampled around the start, the middle and the end of the slice,
+/// which keeps the pivot away from the extremes on organ-pipe and other patterned input.
+/// Slices shorter than [`NINTHER_THRESHOLD`] elements use [`median_of_three`].
+#[inline]
+pub fn ninther<T: Ord>(s: &mut NonEmptySlice<T>) -> usize {
+    ninther_by(s, &mut T::cmp)
+}
+
+/// Minimum length of the slice for [`ninther`] to sample nine elements.
+pub const NINTHER_THRESHOLD: usize = 40;
+
+/// A [`PivotStrategy`] that reports every move of the elements of the slice passed to it.
+///
+/// Co-permuting selection, such as [`quickselect_with_swap`](crate::quickselect_with_swap),
+/// replays the swaps on other slices, so a strategy that moved elements without reporting them
+/// to `on_swap` in [`PivotStrategy::pivot_by`] would break their alignment. Most strategies do
+/// not move elements at all, while [`MedianOfMedians`] reports the swaps of its group sorts.
+///
+/// Closures cannot promise it, since they do not receive `on_swap`, so these functions take the
+/// strategies of this module as the types below, or [`RandomPivot`].
+pub trait ReportsSwaps<T>: PivotStrategy<T> {}
+
+/// [`middle_index`] as a [`ReportsSwaps`] strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MiddleIndex;
+
+impl<T> PivotStrategy<T> for MiddleIndex {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        middle_index(s)
+    }
+}
+
+impl<T> ReportsSwaps<T> for MiddleIndex {}
+
+/// [`first_index`] as a [`ReportsSwaps`] strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FirstIndex;
+
+impl<T> PivotStrategy<T> for FirstIndex {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        first_index(s)
+    }
+}
+
+impl<T> ReportsSwaps<T> for FirstIndex {}
+
+/// [`last_index`] as a [`ReportsSwaps`] strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LastIndex;
+
+impl<T> PivotStrategy<T> for LastIndex {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        last_index(s)
+    }
+}
+
+impl<T> ReportsSwaps<T> for LastIndex {}
+
+/// [`median_of_three`] as a [`ReportsSwaps`] strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MedianOfThree;
+
+impl<T> PivotStrategy<T> for MedianOfThree {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        median_of_three_by(s, compare)
+    }
+}
+
+impl<T> ReportsSwaps<T> for MedianOfThree {}
+
+/// [`ninther`] as a [`ReportsSwaps`] strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Ninther;
+
+impl<T> PivotStrategy<T> for Ninther {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        ninther_by(s, compare)
+    }
+}
+
+impl<T> ReportsSwaps<T> for Ninther {}
+
+/// [`median_of_medians`] as a [`ReportsSwaps`] strategy.
+///
+/// It reorders the slice, and reports the moves as swaps, to an
+/// [`Observer`](crate::stats::Observer) or to co-permuting selection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MedianOfMedians;
+
+impl<T> PivotStrategy<T> for MedianOfMedians {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        median_of_medians_by(s, compare, on_swap)
+    }
+}
+
+impl<T> ReportsSwaps<T> for MedianOfMedians {}
+
+/// [`median_of_three`] with a comparator function.
+pub(crate) fn median_of_three_by<T, F>(s: &[T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = s.len();
+    if len < 3 {
+        return len / 2;
+    }
+    median_index_by(s, [0, len / 2, len - 1], compare)
+}
+
+/// [`ninther`] with a comparator function.
+pub(crate) fn ninther_by<T, F>(s: &[T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = s.len();
+    if len < NINTHER_THRESHOLD {
+        return median_of_three_by(s, compare);
+    }
+    let step = len / 8;
+    let mid = len / 2;
+    let last = len - 1;
+    let medians = [
+        median_index_by(s, [0, step, 2 * step], compare),
+        median_index_by(s, [mid - step, mid, mid + step], compare),
+        median_index_by(s, [last - 2 * step, last - step, last], compare),
+    ];
+    median_index_by(s, medians, compare)
+}
+
+/// Index of an approximate median found with the
+/// [median of medians](https://en.wikipedia.org/wiki/Median_of_medians) algorithm (BFPRT).
+///
+/// The pivot is guaranteed to have at least 30% of the elements on each side, which bounds
+/// the total work of the selection to O(n) regardless of the input.
+/// It is more expensive than the other strategies, see [`quickselect_linear`](crate::quickselect_linear).
+///
+/// The elements of `s` are reordered: each group of 5 elements is sorted and
+/// its median is moved to the front of the slice. No memory is allocated.
+#[inline]
+pub fn median_of_medians<T: Ord>(s: &mut NonEmptySlice<T>) -> usize {
+    median_of_medians_by(s, &mut T::cmp, &mut |_, _| {})
+}
+
+/// [`median_of_medians`] with a comparator function, reporting its swaps to `on_swap`.
+pub(crate) fn median_of_medians_by<T, F, S>(
+    s: &mut NonEmptySlice<T>,
+    compare: &mut F,
+    on_swap: &mut S,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    S: FnMut(usize, usize),
+{
+    median_of_medians_at(s, 0, compare, on_swap)
+}
+
+/// [`median_of_medians_by`] for a slice starting at index `offset`,
+/// which is added to the indices reported to `on_swap`.
+pub(crate) fn median_of_medians_at<T, F, S>(
+    s: &mut NonEmptySlice<T>,
+    offset: usize,
+    compare: &mut F,
+    on_swap: &mut S,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    S: FnMut(usize, usize),
+{
+    const GROUP: usize = 5;
+
+    let len = s.len().get();
+    if len <= GROUP {
+        insertion_sort_by(s, offset, compare, on_swap);
+        return len / 2;
+    }
+
+    let groups = len / GROUP;
+    for i in 0..groups {
+        let start = i * GROUP;
+        insertion_sort_by(
+            &mut s[start..start + GROUP],
+            offset + start,
+            compare,
+            on_swap,
+        );
+        s.swap(i, start + GROUP / 2);
+        on_swap(offset + i, offset + start + GROUP / 2);
+    }
+
+    // Safety: groups >= 1 because len > GROUP, and groups / 2 < groups
+    let medians = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..groups]) };
+    unsafe { crate::select_linear_at(medians, groups / 2, offset, compare, on_swap) };
+    groups / 2
+}
+
+/// Sorts the group `s`, which starts at index `offset`, reporting its swaps.
+fn insertion_sort_by<T, F, S>(s: &mut [T], offset: usize, compare: &mut F, on_swap: &mut S)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    S: FnMut(usize, usize),
+{
+    for i in 1..s.len() {
+        let mut j = i;
+        while j > 0 && compare(&s[j], &s[j - 1]) == Ordering::Less {
+            s.swap(j - 1, j);
+            on_swap(offset + j - 1, offset + j);
+            j -= 1;
+        }
+    }
+}
+
+/// Returns whichever of the three indices holds the median of their elements.
+#[inline]
+fn median_index_by<T, F>(s: &[T], [a, b, c]: [usize; 3], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let less = |compare: &mut F, i: usize, j: usize| compare(&s[i], &s[j]) == Ordering::Less;
+
+    if less(compare, a, b) {
+        // a < b
+        if less(compare, b, c) {
+            b
+        } else if less(compare, a, c) {
+            c
+        } else {
+            a
+        }
+    } else {
+        // b <= a
+        if less(compare, a, c) {
+            a
+        } else if less(compare, b, c) {
+            c
+        } else {
+            b
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{quickselect, test_data::patterns};
+
+    const LEN: usize = 1000;
+
+    /// Runs quickselect on every pattern and checks that the result is correct
+    /// and that the number of iterations stays logarithmic, except for `unbounded` patterns.
+    fn check(get_pivot: fn(&mut NonEmptySlice<u32>) -> usize, unbounded: &[&str]) {
+        for (name, data) in patterns::<LEN>() {
+            for k in [0, 1, LEN / 4, LEN / 2, LEN - 2, LEN - 1] {
+                let mut arr = data;
+                let mut iterations = 0;
+                let counting = |s: &mut NonEmptySlice<u32>| {
+                    iterations += 1;
+                    get_pivot(s)
+                };
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                assert_eq!(*quickselect(s, k, counting), k as u32, "{name}, k = {k}");
+                if !unbounded.contains(&name) {
+                    assert!(iterations <= 40, "{name}, k = {k}: {iterations} iterations");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn median_of_three_patterns() {
+        check(median_of_three, &["organ pipe"]);
+    }
+
+    #[test]
+    fn ninther_patterns() {
+        check(ninther, &[]);
+    }
+
+    #[test]
+    fn median_of_medians_patterns() {
+        check(median_of_medians, &[]);
+    }
+
+    #[test]
+    fn median_of_medians_bounds() {
+        for (name, data) in patterns::<LEN>() {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let pivot = arr[median_of_medians(s)] as usize;
+            assert!(
+                (3 * LEN / 10..7 * LEN / 10).contains(&pivot),
+                "{name}: {pivot}"
+            );
+        }
+    }
+
+    #[test]
+    fn small_slices() {
+        for len in 1..=NINTHER_THRESHOLD {
+            let mut arr = [0; NINTHER_THRESHOLD];
+            let s = NonEmptySlice::new_mut(&mut arr[..len]).unwrap();
+            assert!(median_of_three(s) < len);
+            assert!(ninther(s) < len);
+            assert!(median_of_medians(s) < len);
+        }
+    }
+
+    #[test]
+    fn median_of_three_picks_median() {
+        for arr in [
+            [1, 2, 3],
+            [1, 3, 2],
+            [2, 1, 3],
+            [2, 3, 1],
+            [3, 1, 2],
+            [3, 2, 1],
+        ] {
+            let i = median_index_by(&arr, [0, 1, 2], &mut i32::cmp);
+            assert_eq!(arr[i], 2, "{arr:?}");
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use core::cmp::Ordering;
+
+use not_empty::NonEmptySlice;
+
+use super::{PivotStrategy, ReportsSwaps};
+
+/// Picks a pseudo-random index of the slice.
+///
+/// The generator is [SplitMix64](https://prng.di.unimi.it/splitmix64.c): it is small, fast and
+/// good enough for pivot selection, but it is **not** cryptographically secure.
+/// The same seed always yields the same sequence of pivots, so selections are reproducible.
+///
+/// The state advances with every pivot. Pass the strategy by mutable reference to keep
+/// advancing the same sequence across several selections.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RandomPivot {
+    state: u64,
+}
+
+impl RandomPivot {
+    /// Creates a generator from a fixed seed.
+    #[inline]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator seeded from the randomness that the standard library uses for
+    /// [`HashMap`](std::collections::HashMap), which the operating system provides.
+    #[cfg(feature = "std")]
+    pub fn from_entropy() -> Self {
+        use std::hash::{BuildHasher, RandomState};
+
+        Self::new(RandomState::new().hash_one(0u64))
+    }
+
+    /// Returns the next pseudo-random number.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number in `0..n`.
+    ///
+    /// Uses a widening multiplication rather than a modulo, see
+    /// [Lemire's article](https://lemire.me/blog/2016/06/27/a-fast-alternative-to-the-modulo-reduction/).
+    #[inline]
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+impl<T> PivotStrategy<T> for RandomPivot {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        self.below(s.len().get())
+    }
+}
+
+impl<T> PivotStrategy<T> for &mut RandomPivot {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        _k: usize,
+        _iteration: usize,
+        _compare: &mut F,
+        _on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        self.below(s.len().get())
+    }
+}
+
+impl<T> ReportsSwaps<T> for RandomPivot {}
+
+impl<T> ReportsSwaps<T> for &mut RandomPivot {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quickselect_with_strategy;
+
+    #[test]
+    fn reproducible() {
+        let mut a = RandomPivot::new(7);
+        let mut b = RandomPivot::new(7);
+        for n in 1..1000 {
+            let i = a.below(n);
+            assert!(i < n);
+            assert_eq!(i, b.below(n));
+        }
+        assert_ne!(
+            RandomPivot::new(1).next_u64(),
+            RandomPivot::new(2).next_u64()
+        );
+    }
+
+    #[test]
+    fn selects() {
+        let sorted: [u32; 1000] = core::array::from_fn(|i| i as u32);
+        let mut random = RandomPivot::new(0);
+        for k in [0, 1, 500, 998, 999] {
+            let mut arr = sorted;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(*quickselect_with_strategy(s, k, &mut random), k as u32);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_entropy() {
+        let mut random = RandomPivot::from_entropy();
+        assert!(random.below(10) < 10);
+    }
+}
@@ -0,0 +1,130 @@
+//! Selection over iterators.
+
+use core::cmp::Ordering;
+use std::vec::Vec;
+
+use not_empty::NonEmptySlice;
+
+use crate::{
+    introselect_by,
+    quantile::{self, Numeric, QuantileError, QuantileMethod},
+};
+
+/// Selection methods for iterators.
+///
+/// The elements are collected into a [`Vec`], using the size hint of the iterator, and selected
+/// in with [`introselect`](crate::introselect). Empty inputs and out of bounds ranks return
+/// [`None`] or an error instead of panicking.
+///
+/// # Examples
+///
+/// ```
+/// use alg_quickselect::IteratorExt;
+///
+/// let lengths = ["pear", "apple", "fig", "cherry"].iter().map(|w| w.len());
+/// assert_eq!(lengths.clone().nth_smallest(1), Some(4));
+/// assert_eq!(lengths.median(), Some(4.5));
+///
+/// assert_eq!(core::iter::empty::<u32>().median(), None);
+/// ```
+pub trait IteratorExt: Iterator + Sized {
+    /// Returns the k-th smallest element of the iterator, or [`None`] if it has `k` elements
+    /// or fewer.
+    fn nth_smallest(self, k: usize) -> Option<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.nth_smallest_by(k, Self::Item::cmp)
+    }
+
+    /// [`nth_smallest`](IteratorExt::nth_smallest) with a comparator function,
+    /// see [`quickselect_by`](crate::quickselect_by).
+    fn nth_smallest_by(
+        self,
+        k: usize,
+        compare: impl FnMut(&Self::Item, &Self::Item) -> Ordering,
+    ) -> Option<Self::Item> {
+        let mut v: Vec<_> = self.collect();
+        if k >= v.len() {
+            return None;
+        }
+        let s = NonEmptySlice::new_mut(&mut v).ok()?;
+        introselect_by(s, k, compare);
+        Some(v.swap_remove(k))
+    }
+
+    /// Returns the median of the iterator, or [`None`] if it is empty.
+    ///
+    /// The median is NaN if the iterator yields NaN, see [`quantile::median`].
+    fn median(self) -> Option<f64>
+    where
+        Self::Item: Numeric,
+    {
+        let mut v: Vec<_> = self.collect();
+        NonEmptySlice::new_mut(&mut v).ok().map(quantile::median)
+    }
+
+    /// Returns the `q`-th quantile of the iterator.
+    ///
+    /// See [`quantile::quantile`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuantileError::Empty`] if the iterator is empty,
+    /// [`QuantileError::OutOfRange`] if `q` is NaN or outside of `[0, 1]`,
+    /// and [`QuantileError::Nan`] if the iterator yields NaN.
+    fn quantile(self, q: f64, method: QuantileMethod) -> Result<f64, QuantileError>
+    where
+        Self::Item: Numeric,
+    {
+        let mut v: Vec<_> = self.collect();
+        let s = NonEmptySlice::new_mut(&mut v).map_err(|_| QuantileError::Empty)?;
+        quantile::quantile(s, q, method)
+    }
+}
+
+impl<I: Iterator> IteratorExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::shuffled;
+
+    #[test]
+    fn nth_smallest() {
+        let it = shuffled::<100>(1).into_iter();
+        for k in 0..100 {
+            assert_eq!(it.clone().nth_smallest(k), Some(k as u32));
+        }
+        assert_eq!(it.clone().nth_smallest(100), None);
+        assert_eq!(it.nth_smallest_by(0, |a, b| b.cmp(a)), Some(99));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(core::iter::empty::<u32>().nth_smallest(0), None);
+        assert_eq!(core::iter::empty::<u32>().median(), None);
+        assert_eq!(
+            core::iter::empty::<f64>().quantile(0.5, QuantileMethod::Linear),
+            Err(QuantileError::Empty)
+        );
+    }
+
+    #[test]
+    fn quantile() {
+        let it = [1.0, 2.0, 3.0, 4.0].into_iter();
+        assert_eq!(it.clone().median(), Some(2.5));
+        assert_eq!(it.clone().quantile(0.25, QuantileMethod::Linear), Ok(1.75));
+        assert_eq!(
+            it.quantile(2.0, QuantileMethod::Linear),
+            Err(QuantileError::OutOfRange { q: 2.0 })
+        );
+
+        let it = [1.0, f64::NAN, 2.0].into_iter();
+        assert!(it.clone().median().unwrap().is_nan());
+        assert_eq!(
+            it.quantile(0.5, QuantileMethod::Linear),
+            Err(QuantileError::Nan { index: 1 })
+        );
+    }
+}
@@ -1,4 +1,4 @@
-#![feature(slice_swap_unchecked)]
+#![cfg_attr(feature = "nightly", feature(slice_swap_unchecked))]
 #![no_std]
 // lints
 #![deny(missing_docs)]
@@ -8,41 +8,208 @@
 #![warn(rustdoc::invalid_codeblock_attributes)]
 //! TODO: crate-level docs
 
-use core::cmp::Ordering;
+#[cfg(feature = "std")]
+extern crate std;
 
+use core::{cell::RefCell, cmp::Ordering, convert::Infallible, ops::Range};
+
+use companions::Companions;
+use get_pivot::{PivotStrategy, ReportsSwaps};
 use not_empty::NonEmptySlice;
+use partition::{Block, PartitionScheme, ThreeWay};
+use stats::{Observer, SelectStats};
 
 // pub mod variantions;
+mod argselect;
+pub mod companions;
+mod error;
+pub mod float;
+mod floyd_rivest;
 pub mod get_pivot;
+#[cfg(feature = "std")]
+mod iter;
+mod multiselect;
+pub mod partition;
+pub mod quantile;
+mod slice;
+pub mod stats;
+#[cfg(test)]
+mod test_data;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod top_k;
 
-/// Partitions the given mutable slice `s` around a pivot element selected at `pivot_index`.
+#[cfg(feature = "std")]
+pub use argselect::{argselect, argselect_by};
+pub use argselect::{argselect_by_in, argselect_in};
+pub use error::SelectError;
+pub use floyd_rivest::{floyd_rivest, floyd_rivest_by};
+#[cfg(feature = "std")]
+pub use iter::IteratorExt;
+pub use multiselect::{multiselect, multiselect_by};
+pub use slice::SliceExt;
+pub use top_k::{
+    bottom_k, bottom_k_by, bottom_k_by_key, bottom_k_sorted, bottom_k_sorted_by,
+    bottom_k_sorted_by_key, sort_range, sort_range_by, sort_range_by_key, top_k, top_k_by,
+    top_k_by_key, top_k_sorted, top_k_sorted_by, top_k_sorted_by_key,
+};
+
+/// The selection loop shared by every selection function of the crate.
+///
+/// `get_pivot` receives the current subslice, the rank relative to it, the iteration number,
+/// the comparator and a callback for its swaps. `scheme` partitions the subslice around the pivot.
 ///
-/// The function rearranges the elements of the slice such that all elements less than or equal to
-/// the pivot element are placed before it, and all elements greater than it are placed after it.
-/// The pivot element is moved to its final sorted position.
+/// # Safety
+///
+/// `k` MUST be less than the length of the slice `s`.
+/// `get_pivot` MUST return an index less than the length of the slice passed to it.
+unsafe fn select_unchecked<'a, T, F>(
+    s: &'a mut NonEmptySlice<T>,
+    k: usize,
+    compare: &mut F,
+    get_pivot: impl PivotStrategy<T>,
+    scheme: impl PartitionScheme,
+) -> &'a mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    unsafe { select_observed_unchecked(s, k, compare, get_pivot, scheme, &mut ()) }
+}
+
+/// [`select_unchecked`] that reports the swaps, pivots and partitions to `observer`,
+/// with indices in the original slice.
 ///
 /// # Safety
 ///
-/// `pivot_index` MUST be less than the length of the slice `s`.
+/// Same as [`select_unchecked`].
+unsafe fn select_observed_unchecked<'a, T, F>(
+    s: &'a mut NonEmptySlice<T>,
+    k: usize,
+    compare: &mut F,
+    get_pivot: impl PivotStrategy<T>,
+    scheme: impl PartitionScheme,
+    observer: &mut impl Observer,
+) -> &'a mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let check = |_, _, _| Ok::<_, Infallible>(());
+    match unsafe { try_select_unchecked(s, k, compare, get_pivot, check, scheme, observer) } {
+        Ok(result) => result,
+        Err(never) => match never {},
+    }
+}
+
+/// [`select_observed_unchecked`] that stops at the first error returned by `check`.
+///
+/// `check` receives the length of the subslice, the index returned by `get_pivot` and the
+/// iteration number.
 ///
-/// # Note
+/// # Safety
 ///
-/// This is a private function, do not expose it to the public API.
-unsafe fn partition_unchecked<T: Ord>(s: &mut NonEmptySlice<T>, pivot_index: usize) -> usize {
-    debug_assert!(pivot_index < s.len().get());
+/// `k` MUST be less than the length of the slice `s`.
+/// `check` MUST return an error if the index returned by `get_pivot` is out of bounds of the
+/// slice passed to it.
+unsafe fn try_select_unchecked<'a, T, F, E>(
+    mut s: &'a mut NonEmptySlice<T>,
+    mut k: usize,
+    compare: &mut F,
+    mut get_pivot: impl PivotStrategy<T>,
+    mut check: impl FnMut(usize, usize, usize) -> Result<(), E>,
+    mut scheme: impl PartitionScheme,
+    observer: &mut impl Observer,
+) -> Result<&'a mut T, E>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    debug_assert!(k < s.len().get());
+
+    // Index of s[0] in the original slice
+    let mut offset = 0;
+    let mut iteration = 0;
+    loop {
+        let pivot_index = get_pivot.pivot_by(s, k, iteration, compare, &mut |a, b| {
+            observer.swap(offset + a, offset + b)
+        });
+        check(s.len().get(), pivot_index, iteration)?;
+        iteration += 1;
+        debug_assert!(pivot_index < s.len().get());
+        observer.pivot(offset + pivot_index);
+
+        let Range { start: lt, end: gt } =
+            scheme.partition(s, pivot_index, compare, &mut |a, b| {
+                observer.swap(offset + a, offset + b)
+            });
+        // 0 <= lt < gt <= s.len()
+        // and every element in s[lt..gt] is equal to the k-th smallest element if lt <= k < gt
+        debug_assert!(lt < gt && gt <= s.len().get());
+        observer.partition(offset..offset + s.len().get(), offset + lt..offset + gt);
+
+        if k < lt {
+            // Safety condition: 0 < lt <= s.len()
+            s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..lt]) };
+            // lt != 0 because lt > k >= 0
+        } else if k >= gt {
+            // before: 0 <= k < s.len()
+            k -= gt;
+            // after:  0 <= k < s.len() - gt
+            // since k is at least gt, this will never underflow
+            offset += gt;
+
+            // Safety condition: gt < s.len()
+            s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[gt..]) };
+            // transitively: gt <= k < s.len()
+        } else {
+            return Ok(unsafe { s.get_unchecked_mut(k) }); // lt <= k < gt <= s.len()
+        }
+    }
+}
+
+/// Panics if `k` is out of bounds for `s`.
+#[inline]
+fn check_rank<T>(s: &NonEmptySlice<T>, k: usize) {
+    if k >= s.len().get() {
+        panic!(
+            "index out of bounds: the len is {len} but the index is {idx}",
+            len = s.len().get(),
+            idx = k,
+        );
+    }
+}
+
+/// Wraps `get_pivot` to panic if it returns an index out of bounds.
+#[inline]
+fn checked_pivot<P>(get_pivot: P) -> CheckedPivot<P> {
+    CheckedPivot(get_pivot)
+}
 
-    let last_index = s.len().get() - 1;
-    unsafe { s.swap_unchecked(pivot_index, last_index) };
+/// Pivot strategy returned by [`checked_pivot`].
+struct CheckedPivot<P>(P);
 
-    let mut i = 0;
-    for j in 0..last_index {
-        if s[j] <= s[last_index] {
-            unsafe { s.swap_unchecked(i, j) };
-            i += 1;
+impl<T, P: PivotStrategy<T>> PivotStrategy<T> for CheckedPivot<P> {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        k: usize,
+        iteration: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        let pivot_index = self.0.pivot_by(s, k, iteration, compare, on_swap);
+        if pivot_index >= s.len().get() {
+            panic!(
+                "invalid pivot: index out of bounds: the len is {len} but the index is {idx}",
+                len = s.len().get(),
+                idx = pivot_index
+            )
         }
+        pivot_index
     }
-    unsafe { s.swap_unchecked(i, last_index) };
-    i
 }
 
 /// Finds the k-th smallest element in an unsorted, non-empty slice
@@ -51,6 +218,10 @@ unsafe fn partition_unchecked<T: Ord>(s: &mut NonEmptySlice<T>, pivot_index: usi
 /// Works by iteratively partitioning the input slice into smaller parts,
 /// discarding one side of the pivot element until the k-th smallest element is found.
 ///
+/// After the call, the element at index `k` of `s` is the returned one, and `s` is partitioned
+/// around it: elements before it are less than or equal to it, and elements after it are greater
+/// than or equal to it. Use [`quickselect_partition`] to access both sides.
+///
 /// See the [`get_pivot`] module for more information on the parameter of the same name.
 ///
 /// # Panics
@@ -73,54 +244,864 @@ unsafe fn partition_unchecked<T: Ord>(s: &mut NonEmptySlice<T>, pivot_index: usi
 /// assert_eq!(result, &mut 3);
 /// ```
 pub fn quickselect<T: Ord>(
-    mut s: &mut NonEmptySlice<T>,
-    mut k: usize,
-    mut get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
 ) -> &mut T {
-    if k >= s.len().get() {
+    quickselect_by(s, k, T::cmp, get_pivot)
+}
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice
+/// with a comparator function.
+///
+/// This is the equivalent of [`quickselect`] for types that do not implement [`Ord`],
+/// or when a different ordering is desired, mirroring [`slice::sort_by`].
+/// The comparator must define a total ordering for the elements in the slice.
+///
+/// [`slice::sort_by`]: https://doc.rust-lang.org/std/primitive.slice.html#method.sort_by
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, quickselect_by};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// // Find the 2nd largest element
+/// let result = quickselect_by(s, 1, |a, b| b.cmp(a), middle_index);
+/// assert_eq!(result, &mut 4);
+/// ```
+pub fn quickselect_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> &mut T {
+    quickselect_by_with_strategy(s, k, compare, get_pivot)
+}
+
+/// [`quickselect`] with a [`PivotStrategy`] in place of a closure.
+///
+/// A strategy can keep state across calls, like [`RandomPivot`](get_pivot::RandomPivot),
+/// and sees the rank being searched for and the iteration number.
+/// See the [`get_pivot`] module for more information.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::RandomPivot, quickselect_with_strategy};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(quickselect_with_strategy(s, 2, RandomPivot::new(42)), &mut 3);
+/// ```
+pub fn quickselect_with_strategy<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+) -> &mut T {
+    quickselect_by_with_strategy(s, k, T::cmp, get_pivot)
+}
+
+/// [`quickselect_with_strategy`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_by_with_strategy<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl PivotStrategy<T>,
+) -> &mut T {
+    check_rank(s, k);
+    unsafe { select_unchecked(s, k, &mut compare, checked_pivot(get_pivot), Block) }
+}
+
+/// [`quickselect`] that returns an error instead of panicking.
+///
+/// # Errors
+///
+/// Returns [`SelectError::RankOutOfBounds`] if `k` is out of bounds for the given slice `s`,
+/// and [`SelectError::InvalidPivot`] if the index returned by `get_pivot` is out of bounds
+/// for the input slice passed to it. In the latter case, `s` may be partially partitioned.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, try_quickselect, SelectError};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(try_quickselect(s, 2, middle_index), Ok(&mut 3));
+/// assert_eq!(
+///     try_quickselect(s, 5, middle_index),
+///     Err(SelectError::RankOutOfBounds { len: 5, k: 5 })
+/// );
+/// assert_eq!(
+///     try_quickselect(s, 0, |s| s.len().get()),
+///     Err(SelectError::InvalidPivot { len: 5, idx: 5, iteration: 0 })
+/// );
+/// ```
+pub fn try_quickselect<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> Result<&mut T, SelectError> {
+    try_quickselect_by(s, k, T::cmp, get_pivot)
+}
+
+/// [`quickselect_by`] that returns an error instead of panicking.
+///
+/// # Errors
+///
+/// Same as [`try_quickselect`].
+pub fn try_quickselect_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> Result<&mut T, SelectError> {
+    try_quickselect_by_with_strategy(s, k, compare, get_pivot)
+}
+
+/// [`quickselect_with_strategy`] that returns an error instead of panicking.
+///
+/// # Errors
+///
+/// Same as [`try_quickselect`].
+pub fn try_quickselect_with_strategy<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+) -> Result<&mut T, SelectError> {
+    try_quickselect_by_with_strategy(s, k, T::cmp, get_pivot)
+}
+
+/// [`quickselect_by_with_strategy`] that returns an error instead of panicking.
+///
+/// # Errors
+///
+/// Same as [`try_quickselect`].
+pub fn try_quickselect_by_with_strategy<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl PivotStrategy<T>,
+) -> Result<&mut T, SelectError> {
+    let len = s.len().get();
+    if k >= len {
+        return Err(SelectError::RankOutOfBounds { len, k });
+    }
+    let check = |len, idx, iteration| {
+        if idx >= len {
+            return Err(SelectError::InvalidPivot {
+                len,
+                idx,
+                iteration,
+            });
+        }
+        Ok(())
+    };
+    unsafe { try_select_unchecked(s, k, &mut compare, get_pivot, check, Block, &mut ()) }
+}
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice
+/// with a key extraction function.
+///
+/// This is the equivalent of [`quickselect`] ordering elements by the key
+/// returned from `f`, mirroring [`slice::sort_by_key`].
+/// The key function is called on every comparison; if it is expensive,
+/// consider caching the keys.
+///
+/// [`slice::sort_by_key`]: https://doc.rust-lang.org/std/primitive.slice.html#method.sort_by_key
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, quickselect_by_key};
+///
+/// let mut arr = [(4, 'a'), (2, 'b'), (5, 'c'), (1, 'd'), (3, 'e')];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let result = quickselect_by_key(s, 0, |&(n, _)| n, middle_index);
+/// assert_eq!(result, &mut (1, 'd'));
+/// ```
+pub fn quickselect_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> &mut T {
+    quickselect_by(s, k, |a, b| f(a).cmp(&f(b)), get_pivot)
+}
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice
+/// with a key extraction function, calling it only once per element.
+///
+/// This is the equivalent of [`quickselect_by_key`] for expensive key functions, mirroring
+/// [`slice::sort_by_cached_key`]. The keys are computed once into a scratch buffer of
+/// `(key, index)` pairs, the selection runs over the pairs, and the original slice is then
+/// permuted into the same partitioned state.
+///
+/// Because the selection runs over the pairs, `get_pivot` receives the pairs and not the
+/// elements of `s`. Generic strategies such as those in the [`get_pivot`] module work as is.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, quickselect_by_cached_key};
+///
+/// let mut arr = ["Delta", "alpha", "Charlie", "bravo"];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let result = quickselect_by_cached_key(s, 2, |s| s.to_lowercase(), middle_index);
+/// assert_eq!(result, &mut "Charlie");
+/// ```
+#[cfg(feature = "std")]
+pub fn quickselect_by_cached_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    f: impl FnMut(&T) -> K,
+    get_pivot: impl FnMut(&mut NonEmptySlice<(K, usize)>) -> usize,
+) -> &mut T {
+    use std::vec::Vec;
+
+    check_rank(s, k);
+
+    let mut indices: Vec<_> = s
+        .iter()
+        .map(f)
+        .enumerate()
+        .map(|(i, key)| (key, i))
+        .collect();
+    // Safety: `indices` has one entry per element of `s`, which is not empty
+    let pairs = unsafe { NonEmptySlice::new_mut_unchecked(&mut indices[..]) };
+    quickselect(pairs, k, get_pivot);
+
+    // Apply the permutation in place, the same way `slice::sort_by_cached_key` does:
+    // position `i` should hold the element originally at `indices[i].1`, which may have
+    // already been moved by an earlier swap, so follow the chain until it is found.
+    for i in 0..s.len().get() {
+        let mut index = indices[i].1;
+        while index < i {
+            index = indices[index].1;
+        }
+        indices[i].1 = index;
+        s.swap(i, index);
+    }
+    &mut s[k]
+}
+
+/// Splits a slice that has been partitioned around index `k`.
+///
+/// # Safety
+///
+/// `k` MUST be less than the length of the slice `s`.
+unsafe fn split_at_rank<T>(s: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T]) {
+    debug_assert!(k < s.len());
+
+    let (left, rest) = unsafe { s.split_at_mut_unchecked(k) };
+    // Safety: k < s.len(), so rest is not empty
+    let (nth, right) = unsafe { rest.split_first_mut().unwrap_unchecked() };
+    (left, nth, right)
+}
+
+/// Reorders the slice such that the k-th smallest element is at its final sorted position,
+/// and returns the whole partitioned slice, like [`slice::select_nth_unstable`].
+///
+/// Returns a triplet of:
+/// - the elements before index `k`, all of which are less than or equal to the k-th smallest,
+/// - the k-th smallest element,
+/// - the elements after index `k`, all of which are greater than or equal to the k-th smallest.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, quickselect_partition};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let (left, nth, right) = quickselect_partition(s, 2, middle_index);
+/// assert!(left.iter().all(|x| x <= nth));
+/// assert_eq!(nth, &mut 3);
+/// assert!(right.iter().all(|x| x >= nth));
+/// ```
+pub fn quickselect_partition<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    quickselect_partition_by(s, k, T::cmp, get_pivot)
+}
+
+/// Equivalent of [`quickselect_partition`] with a comparator function,
+/// see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_partition_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    // The k-th smallest element always ends up at index k of the original slice,
+    // no matter how far the selection narrowed it down.
+    quickselect_by(s, k, compare, get_pivot);
+    // Safety: quickselect_by panics if k is out of bounds
+    unsafe { split_at_rank(s, k) }
+}
+
+/// Equivalent of [`quickselect_partition`] with a key extraction function,
+/// see [`quickselect_by_key`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_partition_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> (&mut [T], &mut T, &mut [T]) {
+    quickselect_partition_by(s, k, |a, b| f(a).cmp(&f(b)), get_pivot)
+}
+
+/// [`quickselect`] with the given partition scheme in place of [`Block`].
+///
+/// See the [`partition`] module for the available schemes.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::median_of_three, partition::ThreeWay, quickselect_with_scheme};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(quickselect_with_scheme(s, 2, median_of_three, ThreeWay), &mut 3);
+/// ```
+pub fn quickselect_with_scheme<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+    scheme: impl PartitionScheme,
+) -> &mut T {
+    quickselect_by_with_scheme(s, k, T::cmp, get_pivot, scheme)
+}
+
+/// [`quickselect_by`] with the given partition scheme in place of [`Block`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_by_with_scheme<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl PivotStrategy<T>,
+    scheme: impl PartitionScheme,
+) -> &mut T {
+    check_rank(s, k);
+    unsafe { select_unchecked(s, k, &mut compare, checked_pivot(get_pivot), scheme) }
+}
+
+/// [`quickselect`] that calls `on_swap` with the indices of every pair of elements it swaps,
+/// in the order of the swaps.
+///
+/// Replaying the swaps on parallel slices keeps them aligned with `s`.
+/// See [`quickselect_with_companions`] to pass the slices directly.
+///
+/// `get_pivot` must be a [`ReportsSwaps`] strategy, so that its own moves are replayed too.
+/// [`MedianOfMedians`](get_pivot::MedianOfMedians) keeps the selection worst-case linear.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::MedianOfThree, quickselect_with_swap};
+///
+/// let mut keys = [4, 2, 5, 1, 3];
+/// let mut rows = [0, 1, 2, 3, 4];
+/// let mut s = NonEmptySlice::new_mut(&mut keys).unwrap();
+///
+/// assert_eq!(quickselect_with_swap(s, 2, MedianOfThree, |a, b| rows.swap(a, b)), &mut 3);
+/// assert_eq!(rows[2], 4);
+/// ```
+pub fn quickselect_with_swap<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl ReportsSwaps<T>,
+    on_swap: impl FnMut(usize, usize),
+) -> &mut T {
+    quickselect_by_with_swap(s, k, T::cmp, get_pivot, on_swap)
+}
+
+/// [`quickselect_with_swap`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_by_with_swap<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl ReportsSwaps<T>,
+    on_swap: impl FnMut(usize, usize),
+) -> &mut T {
+    check_rank(s, k);
+    let get_pivot = checked_pivot(get_pivot);
+    let mut observer = OnSwap(on_swap);
+    unsafe { select_observed_unchecked(s, k, &mut compare, get_pivot, Block, &mut observer) }
+}
+
+/// Observer forwarding the swaps to a callback.
+struct OnSwap<S>(S);
+
+impl<S: FnMut(usize, usize)> Observer for OnSwap<S> {
+    #[inline(always)]
+    fn swap(&mut self, a: usize, b: usize) {
+        (self.0)(a, b);
+    }
+}
+
+/// [`quickselect`] that applies every swap it makes in `s` to the `companions` slices too,
+/// so that their rows stay aligned with `s`.
+///
+/// See the [`companions`] module for the accepted companions,
+/// and [`quickselect_with_swap`] for the restriction on `get_pivot`.
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for the given slice `s`,
+/// or if the companion slices do not have the same length as `s`.
+/// Additionally, panics if the index returned by `get_pivot` is out of bounds
+/// for the input slice passed to it.
+pub fn quickselect_with_companions<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl ReportsSwaps<T>,
+    companions: impl Companions,
+) -> &mut T {
+    quickselect_by_with_companions(s, k, T::cmp, get_pivot, companions)
+}
+
+/// [`quickselect_with_companions`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect_with_companions`].
+pub fn quickselect_by_with_companions<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl ReportsSwaps<T>,
+    mut companions: impl Companions,
+) -> &mut T {
+    if !companions.has_len(s.len().get()) {
         panic!(
-            "index out of bounds: the len is {len} but the index is {idx}",
-            len = s.len().get(),
-            idx = k,
+            "companion length mismatch: every companion must have the len {len}",
+            len = s.len()
         );
     }
+    quickselect_by_with_swap(s, k, compare, get_pivot, |a, b| companions.swap(a, b))
+}
+
+/// [`quickselect`] that reports the comparisons, swaps, pivots and partitions to `observer`.
+///
+/// See the [`stats`] module for the events, and [`quickselect_with_stats`] to count them.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_with_observer<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+    observer: impl Observer,
+) -> &mut T {
+    quickselect_by_with_observer(s, k, T::cmp, get_pivot, observer)
+}
+
+/// [`quickselect_with_observer`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+pub fn quickselect_by_with_observer<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl PivotStrategy<T>,
+    observer: impl Observer,
+) -> &mut T {
+    check_rank(s, k);
+    // The comparator is passed to the scheme along with the swap callback, so both share it
+    let observer = RefCell::new(observer);
+    let mut compare = |a: &T, b: &T| {
+        observer.borrow_mut().compare();
+        compare(a, b)
+    };
+    let get_pivot = checked_pivot(get_pivot);
+    let mut observer = SharedObserver(&observer);
+    unsafe { select_observed_unchecked(s, k, &mut compare, get_pivot, Block, &mut observer) }
+}
+
+/// Observer forwarding the events to an observer shared with the comparator.
+struct SharedObserver<'a, O>(&'a RefCell<O>);
+
+impl<O: Observer> Observer for SharedObserver<'_, O> {
+    #[inline(always)]
+    fn swap(&mut self, a: usize, b: usize) {
+        self.0.borrow_mut().swap(a, b);
+    }
+
+    #[inline(always)]
+    fn pivot(&mut self, index: usize) {
+        self.0.borrow_mut().pivot(index);
+    }
+
+    #[inline(always)]
+    fn partition(&mut self, range: Range<usize>, equal: Range<usize>) {
+        self.0.borrow_mut().partition(range, equal);
+    }
+}
+
+/// [`quickselect`] that also returns the counts of comparisons, swaps, pivots and partitions.
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::middle_index, quickselect_with_stats};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let (result, stats) = quickselect_with_stats(s, 2, middle_index);
+/// assert_eq!(result, &mut 3);
+/// assert_eq!((stats.pivot_calls, stats.partitions), (4, 4));
+/// // Subslices of 5, 4, 3 and 2 elements
+/// assert_eq!(stats.elements_partitioned, 14);
+/// ```
+pub fn quickselect_with_stats<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+) -> (&mut T, SelectStats) {
+    let mut stats = SelectStats::default();
+    let result = quickselect_with_observer(s, k, get_pivot, &mut stats);
+    (result, stats)
+}
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice in worst-case linear time.
+///
+/// This is [`quickselect`] with the [`median_of_medians`](get_pivot::median_of_medians) pivot
+/// strategy. It is slower than the other strategies on typical input,
+/// but cannot be driven quadratic by an adversary.
+///
+/// # Panics
+///
+/// Panics if the specified value of `k` is out of bounds for the given slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quickselect_linear;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(quickselect_linear(s, 2), &mut 3);
+/// ```
+pub fn quickselect_linear<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut T {
+    quickselect_linear_by(s, k, T::cmp)
+}
+
+/// [`quickselect_linear`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Same as [`quickselect_linear`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quickselect_linear_by;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// // Find the 2nd largest element
+/// assert_eq!(quickselect_linear_by(s, 1, |a, b| b.cmp(a)), &mut 4);
+/// ```
+pub fn quickselect_linear_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut T {
+    check_rank(s, k);
+    unsafe { select_linear_at(s, k, 0, &mut compare, &mut |_, _| {}) }
+}
+
+/// [`quickselect_linear`] with a key extraction function, see [`quickselect_by_key`].
+///
+/// # Panics
+///
+/// Same as [`quickselect_linear`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quickselect_linear_by_key;
+///
+/// let mut arr = [(4, 'a'), (2, 'b'), (5, 'c'), (1, 'd'), (3, 'e')];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(quickselect_linear_by_key(s, 0, |&(n, _)| n), &mut (1, 'd'));
+/// ```
+pub fn quickselect_linear_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut T {
+    quickselect_linear_by(s, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// The selection loop of [`quickselect_linear`], reporting the swaps to `on_swap` with `offset`
+/// added to their indices.
+///
+/// [`median_of_medians`](get_pivot::median_of_medians) recurses into this loop with the same
+/// `on_swap`, rather than through [`select_observed_unchecked`], which would wrap it in a new
+/// closure type at every level of the recursion.
+///
+/// # Safety
+///
+/// `k` MUST be less than the length of the slice `s`.
+pub(crate) unsafe fn select_linear_at<'a, T, F, S>(
+    mut s: &'a mut NonEmptySlice<T>,
+    mut k: usize,
+    mut offset: usize,
+    compare: &mut F,
+    on_swap: &mut S,
+) -> &'a mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+    S: FnMut(usize, usize),
+{
+    debug_assert!(k < s.len().get());
 
     loop {
-        let pivot_index = get_pivot(s);
-        if pivot_index >= s.len().get() {
-            panic!(
-                "invalid pivot: index out of bounds: the len is {len} but the index is {idx}",
-                len = s.len().get(),
-                idx = pivot_index
-            )
+        let pivot_index = get_pivot::median_of_medians_at(s, offset, compare, on_swap);
+        let Range { start: lt, end: gt } =
+            ThreeWay.partition(s, pivot_index, compare, &mut |a, b| {
+                on_swap(offset + a, offset + b)
+            });
+
+        if k < lt {
+            // Safety condition: 0 < lt <= s.len(), see try_select_unchecked
+            s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..lt]) };
+        } else if k >= gt {
+            k -= gt;
+            offset += gt;
+            // Safety condition: gt <= k < s.len()
+            s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[gt..]) };
+        } else {
+            return unsafe { s.get_unchecked_mut(k) }; // lt <= k < gt <= s.len()
         }
-        let pivot_index = unsafe { partition_unchecked(s, pivot_index) };
-        // 0 <= pivot_index < s.len()
-        // because if pivot_index >= s.len(), partition would have panicked
-
-        match pivot_index.cmp(&k) {
-            Ordering::Equal => return unsafe { s.get_unchecked_mut(k) }, // 0 <= k < s.len()
-            Ordering::Less => {
-                // before: 0 <= k < s.len()
-                k -= pivot_index + 1;
-                // after:  0 <= k < s.len() - 1
-                // since k is greater than pivot_index (by at least +1), this will never underflow
-
-                // To make sure the new slice is not empty
-                // Safety condition: pivot_index + 1 < s.len()
-                s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[pivot_index + 1..]) };
-                // transitively: pivot_index < k < s.len()
-                // therefore, pivot_index < s.len() - 1
-                // and pivot_index + 1 < s.len()
-                // (using k before because pivot_index does not change)
-            }
-            Ordering::Greater => {
-                // Safety condition: 0 < pivot_index <= s.len()
-                s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..pivot_index]) };
-                // Safe because
-                // 1. pivot_index < s.len()
-                // 2. pivot_index != 0 because pivot_index > k >= 0, so pivot_index is at least 1
-            }
+    }
+}
+
+/// Maximum number of partitions that keep more than 3/4 of the slice
+/// before [`introselect`] falls back to a worst-case linear pivot.
+///
+/// The budget must be constant: a budget growing with the length of the slice,
+/// like the recursion limit of introsort, would only bound the work to O(n log n).
+const INTROSELECT_BUDGET: usize = 16;
+
+/// Finds the k-th smallest element in an unsorted, non-empty slice with
+/// [introselect](https://en.wikipedia.org/wiki/Introselect).
+///
+/// Starts as [`quickselect`] with the [`ninther`](get_pivot::ninther) pivot strategy.
+/// Whenever a partition discards less than a quarter of the slice, a budget is decremented,
+/// and once it is exhausted, the selection continues as [`quickselect_linear`].
+/// This keeps the speed of the cheap strategy on typical input and the O(n) worst case.
+///
+/// The partitions use [`Block`], the fastest scheme of the [`partition`] module.
+///
+/// # Panics
+///
+/// Panics if the specified value of `k` is out of bounds for the given slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::introselect;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(introselect(s, 2), &mut 3);
+/// ```
+pub fn introselect<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut T {
+    introselect_by(s, k, T::cmp)
+}
+
+/// [`introselect`] with a comparator function, see [`quickselect_by`].
+///
+/// # Panics
+///
+/// Panics if the specified value of `k` is out of bounds for the given slice `s`.
+pub fn introselect_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut T {
+    check_rank(s, k);
+    unsafe { introselect_unchecked(s, k, &mut compare, get_pivot::Ninther) }
+}
+
+/// [`introselect`] starting with the given pivot strategy in place of
+/// [`ninther`](get_pivot::ninther).
+///
+/// # Panics
+///
+/// Same as [`quickselect`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::first_index, introselect_with};
+///
+/// // first_index alone is quadratic on sorted input
+/// let mut arr: [u32; 1000] = core::array::from_fn(|i| i as u32);
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(introselect_with(s, 500, first_index), &mut 500);
+/// ```
+pub fn introselect_with<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl PivotStrategy<T>,
+) -> &mut T {
+    check_rank(s, k);
+    unsafe { introselect_unchecked(s, k, &mut T::cmp, checked_pivot(get_pivot)) }
+}
+
+/// The selection loop of [`introselect`].
+///
+/// # Safety
+///
+/// Same as [`select_unchecked`].
+unsafe fn introselect_unchecked<'a, T, F>(
+    s: &'a mut NonEmptySlice<T>,
+    k: usize,
+    compare: &mut F,
+    get_pivot: impl PivotStrategy<T>,
+) -> &'a mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let get_pivot = introselect_pivot(s.len().get(), get_pivot);
+    unsafe { select_unchecked(s, k, compare, get_pivot, Block) }
+}
+
+/// Wraps `get_pivot` with the budget of [`introselect`], for a slice of length `len`.
+///
+/// Once the budget is exhausted, the pivots come from
+/// [`median_of_medians`](get_pivot::median_of_medians).
+pub(crate) fn introselect_pivot<P>(len: usize, get_pivot: P) -> IntroselectPivot<P> {
+    IntroselectPivot {
+        get_pivot,
+        budget: INTROSELECT_BUDGET,
+        previous_len: len,
+    }
+}
+
+/// Pivot strategy returned by [`introselect_pivot`].
+pub(crate) struct IntroselectPivot<P> {
+    get_pivot: P,
+    budget: usize,
+    previous_len: usize,
+}
+
+impl<T, P: PivotStrategy<T>> PivotStrategy<T> for IntroselectPivot<P> {
+    #[inline]
+    fn pivot_by<F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        k: usize,
+        iteration: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        let len = s.len().get();
+        // The first call has nothing to compare against. Under multiselect, the previous slice can
+        // be the smaller sibling of this one, which spends the budget early but keeps the linear
+        // bound
+        if iteration > 0 && self.budget > 0 && len > self.previous_len - self.previous_len / 4 {
+            self.budget -= 1;
+        }
+        self.previous_len = len;
+
+        if self.budget == 0 {
+            get_pivot::median_of_medians_by(s, compare, on_swap)
+        } else {
+            self.get_pivot.pivot_by(s, k, iteration, compare, on_swap)
         }
     }
 }
@@ -134,41 +1115,408 @@ pub fn quickselect<T: Ord>(
 /// of the safe version must be upheld. However, instead of panicking,
 /// violating these conditions is undefined behavior.
 pub unsafe fn quickselect_unchecked<T: Ord>(
-    mut s: &mut NonEmptySlice<T>,
-    mut k: usize,
-    mut get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
 ) -> &mut T {
-    debug_assert!(k < s.len().get());
+    unsafe { quickselect_by_unchecked(s, k, T::cmp, get_pivot) }
+}
 
-    loop {
-        let pivot_index = get_pivot(s);
-        debug_assert!(pivot_index < s.len().get());
-        let pivot_index = unsafe { partition_unchecked(s, pivot_index) };
-        debug_assert!(pivot_index < s.len().get());
+/// Unsafe version of [`quickselect_by`]. It does not perform bounds checks
+/// nor panic when indices are out-of-bounds.
+///
+/// # Safety
+///
+/// See [`quickselect_unchecked`].
+pub unsafe fn quickselect_by_unchecked<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> &mut T {
+    unsafe { select_unchecked(s, k, &mut compare, get_pivot, Block) }
+}
+
+/// Unsafe version of [`quickselect_by_key`]. It does not perform bounds checks
+/// nor panic when indices are out-of-bounds.
+///
+/// # Safety
+///
+/// See [`quickselect_unchecked`].
+pub unsafe fn quickselect_by_key_unchecked<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+    get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+) -> &mut T {
+    unsafe { quickselect_by_unchecked(s, k, |a, b| f(a).cmp(&f(b)), get_pivot) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_pivot::{
+        first_index, last_index, median_of_three, middle_index, MedianOfMedians, MedianOfThree,
+        MiddleIndex,
+    };
+    use crate::test_data::{patterns, random, shuffled};
+
+    #[test]
+    fn it_works() {}
+
+    #[test]
+    fn quickselect_by_reverse() {
+        for k in 0..8 {
+            let mut arr = [3, 7, 0, 5, 1, 6, 2, 4];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(*quickselect_by(s, k, |a, b| b.cmp(a), last_index), 7 - k);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn quickselect_by_cached_key_partitions() {
+        let mut calls = 0;
+        let mut arr = [-3i32, 8, -1, 0, 5, -9, 2, 7, -4, 6];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let key = |n: &i32| {
+            calls += 1;
+            n.abs()
+        };
+        assert_eq!(*quickselect_by_cached_key(s, 4, key, middle_index), -4);
+        assert_eq!(calls, 10);
+        assert!(arr[..4].iter().all(|n| n.abs() <= 4));
+        assert!(arr[5..].iter().all(|n| n.abs() >= 4));
+    }
+
+    #[test]
+    fn quickselect_partition_sides() {
+        let data = [9, 3, 3, 7, 0, 5, 1, 8, 6, 2, 4, 3];
+        let mut sorted = data;
+        sorted.sort_unstable();
+
+        for (k, expected) in sorted.iter().enumerate() {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let (left, nth, right) = quickselect_partition(s, k, middle_index);
+            assert_eq!((left.len(), right.len()), (k, data.len() - k - 1));
+            assert_eq!(nth, expected);
+            assert!(left.iter().all(|x| x <= nth));
+            assert!(right.iter().all(|x| x >= nth));
+        }
+    }
+
+    #[test]
+    fn few_distinct_values() {
+        let mut iterations = 0;
+        let mut arr = [42; 10_000];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let counting = |s: &mut NonEmptySlice<_>| {
+            iterations += 1;
+            first_index(s)
+        };
+        assert_eq!(*quickselect(s, 5000, counting), 42);
+        assert_eq!(iterations, 1);
+
+        let data = shuffled::<1000>(1).map(|x| (x / 250) as u8);
+        for k in [0, 249, 250, 500, 999] {
+            let mut iterations = 0;
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let counting = |s: &mut NonEmptySlice<_>| {
+                iterations += 1;
+                middle_index(s)
+            };
+            assert_eq!(*quickselect(s, k, counting), (k / 250) as u8);
+            assert!(iterations <= 4, "k = {k}: {iterations} iterations");
+            assert!(arr[..k].iter().all(|&x| x <= (k / 250) as u8));
+            assert!(arr[k..].iter().all(|&x| x >= (k / 250) as u8));
+        }
+    }
+
+    #[test]
+    fn quickselect_linear_duplicates() {
+        let mut arr = [7; 1000];
+        for (i, x) in arr.iter_mut().enumerate().step_by(3) {
+            *x = i % 10;
+        }
+        let mut sorted = arr;
+        sorted.sort_unstable();
+
+        for k in [0, 1, 333, 334, 500, 999] {
+            let mut arr = arr;
+            let mut comparisons = 0;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let counting = |a: &usize, b: &usize| {
+                comparisons += 1;
+                a.cmp(b)
+            };
+            assert_eq!(*quickselect_linear_by(s, k, counting), sorted[k]);
+            assert!(
+                comparisons < 20 * 1000,
+                "k = {k}: {comparisons} comparisons"
+            );
+        }
+    }
+
+    #[test]
+    fn introselect_falls_back() {
+        let mut sorted = [0; 2000];
+        for (i, x) in sorted.iter_mut().enumerate() {
+            *x = i / 2;
+        }
+
+        for k in [0, 1, 999, 1000, 1998, 1999] {
+            let mut comparisons = 0;
+            let mut counting = |a: &usize, b: &usize| {
+                comparisons += 1;
+                a.cmp(b)
+            };
+            let mut arr = sorted;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let first = |_: &mut NonEmptySlice<usize>| 0;
+            let result = unsafe { introselect_unchecked(s, k, &mut counting, first) };
+            assert_eq!(*result, k / 2);
+            assert!(
+                comparisons < 50 * 2000,
+                "k = {k}: {comparisons} comparisons"
+            );
+
+            let mut arr = sorted;
+            arr.reverse();
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(*introselect(s, k), k / 2);
+        }
+    }
+
+    #[test]
+    fn companions_stay_aligned() {
+        let mut keys = random::<500>(1, 61);
+        let mut doubled: [u64; 500] = core::array::from_fn(|i| keys[i] as u64 * 2);
+        let mut rows: [usize; 500] = core::array::from_fn(|i| i);
+        let original = keys;
+
+        for k in [0, 1, 250, 498, 499] {
+            let s = NonEmptySlice::new_mut(&mut keys).unwrap();
+            let key = *quickselect_with_companions(s, k, MiddleIndex, (&mut doubled, &mut rows));
+
+            assert_eq!(doubled[k], key as u64 * 2);
+            assert!((0..500).all(|i| doubled[i] == keys[i] as u64 * 2));
+            assert!((0..500).all(|i| keys[i] == original[rows[i]]));
+
+            // The moves of the strategy are replayed as well
+            let s = NonEmptySlice::new_mut(&mut keys).unwrap();
+            let key =
+                *quickselect_with_companions(s, k, MedianOfMedians, (&mut doubled, &mut rows));
+
+            assert_eq!(doubled[k], key as u64 * 2);
+            assert!((0..500).all(|i| doubled[i] == keys[i] as u64 * 2));
+            assert!((0..500).all(|i| keys[i] == original[rows[i]]));
+        }
+    }
 
-        match pivot_index.cmp(&k) {
-            Ordering::Equal => return unsafe { s.get_unchecked_mut(k) },
-            Ordering::Less => {
-                debug_assert!(k < s.len().get());
-                k -= pivot_index + 1;
-                debug_assert!(k < s.len().get() - 1);
+    #[test]
+    #[should_panic = "companion length mismatch"]
+    fn companions_too_short() {
+        let mut keys = [3, 1, 2];
+        let s = NonEmptySlice::new_mut(&mut keys).unwrap();
+        quickselect_with_companions(s, 1, MiddleIndex, &mut [0; 2]);
+    }
+
+    #[test]
+    fn try_quickselect_errors() {
+        let mut arr = [5, 3, 8, 1, 9, 2, 7];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(
+            try_quickselect(s, 7, middle_index),
+            Err(SelectError::RankOutOfBounds { len: 7, k: 7 })
+        );
+
+        // Valid on the whole slice, out of bounds once it shrinks
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let result = try_quickselect(s, 6, |_| 2);
+        assert!(matches!(
+            result,
+            Err(SelectError::InvalidPivot { idx: 2, iteration, .. }) if iteration > 0
+        ));
+
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(try_quickselect(s, 3, middle_index), Ok(&mut 5));
+
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(
+            try_quickselect_by_with_strategy(s, 1, |a, b| b.cmp(a), MedianOfThree),
+            Ok(&mut 8)
+        );
+    }
+
+    #[test]
+    fn stats_match_events() {
+        struct Events {
+            swaps: [usize; 100],
+            ranges: usize,
+            last_equal: Range<usize>,
+        }
+
+        impl Observer for Events {
+            fn swap(&mut self, a: usize, b: usize) {
+                self.swaps.swap(a, b);
+            }
 
-                debug_assert!(pivot_index + 1 < s.len().get());
-                s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[pivot_index + 1..]) };
+            fn partition(&mut self, range: Range<usize>, equal: Range<usize>) {
+                assert!(range.start <= equal.start && equal.end <= range.end);
+                self.ranges += 1;
+                self.last_equal = equal;
             }
-            Ordering::Greater => {
-                debug_assert!(pivot_index < s.len().get());
-                debug_assert!(pivot_index > 0);
-                s = unsafe { NonEmptySlice::new_mut_unchecked(&mut s[..pivot_index]) };
+        }
+
+        let data = shuffled::<100>(1);
+        let mut arr = data;
+        let mut events = Events {
+            swaps: core::array::from_fn(|i| i),
+            ranges: 0,
+            last_equal: 0..0,
+        };
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(
+            *quickselect_with_observer(s, 42, first_index, &mut events),
+            42
+        );
+        assert!((0..100).all(|i| arr[i] == data[events.swaps[i]]));
+        assert!(events.last_equal.contains(&42));
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let (_, stats) = quickselect_with_stats(s, 42, first_index);
+        assert_eq!(stats.partitions, events.ranges);
+        assert_eq!(stats.pivot_calls, events.ranges);
+        assert!(
+            stats.swaps > 0 && stats.comparisons >= stats.elements_partitioned - stats.partitions
+        );
+    }
+
+    #[test]
+    fn stats_see_strategy_types() {
+        struct Swaps([usize; 500]);
+
+        impl Observer for Swaps {
+            fn swap(&mut self, a: usize, b: usize) {
+                self.0.swap(a, b);
             }
         }
+
+        fn count<P: PivotStrategy<u32>>(data: [u32; 500], get_pivot: impl Fn() -> P) {
+            let mut comparisons = 0;
+            let counting = |a: &u32, b: &u32| {
+                comparisons += 1;
+                a.cmp(b)
+            };
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            quickselect_by_with_strategy(s, 100, counting, get_pivot());
+
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let (_, stats) = quickselect_with_stats(s, 100, get_pivot());
+            assert_eq!(stats.comparisons, comparisons);
+
+            let mut arr = data;
+            let mut swaps = Swaps(core::array::from_fn(|i| i));
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            quickselect_with_observer(s, 100, get_pivot(), &mut swaps);
+            assert!((0..500).all(|i| arr[i] == data[swaps.0[i]]));
+        }
+
+        let data = shuffled::<500>(2);
+        count(data, || MedianOfThree);
+        count(data, || MedianOfMedians);
+
+        // The comparisons of functions are not seen
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let (_, function) = quickselect_with_stats(s, 100, median_of_three);
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let (_, strategy) = quickselect_with_stats(s, 100, MedianOfThree);
+        assert_eq!(strategy.pivot_calls, function.pivot_calls);
+        assert!(strategy.comparisons > function.comparisons);
+        assert!(strategy.comparisons - function.comparisons <= 3 * strategy.pivot_calls);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    // use super::*;
+    #[test]
+    fn quickselect_by_key_field() {
+        for k in 0..5 {
+            let mut arr = [(4, 'a'), (2, 'b'), (0, 'c'), (1, 'd'), (3, 'e')];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(quickselect_by_key(s, k, |&(n, _)| n, first_index).0, k);
+
+            let mut arr = [(4, 'a'), (2, 'b'), (0, 'c'), (1, 'd'), (3, 'e')];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let result = unsafe { quickselect_by_key_unchecked(s, k, |&(n, _)| n, middle_index) };
+            assert_eq!(result.0, k);
+        }
+    }
 
     #[test]
-    fn it_works() {}
+    fn schemes_agree() {
+        use crate::get_pivot::ninther;
+        use crate::partition::{Block, Hoare, Lomuto, PartitionScheme, ThreeWay};
+
+        const LEN: usize = 1000;
+
+        /// A pivot function and the number of comparisons per element it must stay within.
+        type Strategy = (&'static str, fn(&mut NonEmptySlice<u32>) -> usize, usize);
+
+        fn check(
+            name: &str,
+            data: [u32; LEN],
+            strategies: &[Strategy],
+            mut scheme: impl PartitionScheme,
+        ) {
+            let mut sorted = data;
+            sorted.sort_unstable();
+
+            for &(strategy, get_pivot, bound) in strategies {
+                for (k, expected) in sorted.iter().enumerate().step_by(37) {
+                    let mut arr = data;
+                    let mut comparisons = 0;
+                    let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                    let compare = |a: &u32, b: &u32| {
+                        comparisons += 1;
+                        a.cmp(b)
+                    };
+                    let result = quickselect_by_with_scheme(s, k, compare, get_pivot, &mut scheme);
+                    assert_eq!(result, expected, "{name}, {strategy}, k = {k}");
+                    assert!(
+                        comparisons <= bound * LEN,
+                        "{name}, {strategy}, k = {k}: {comparisons} comparisons"
+                    );
+
+                    let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                    let result =
+                        quickselect_by_with_scheme(s, k, |a, b| b.cmp(a), get_pivot, &mut scheme);
+                    assert_eq!(
+                        result,
+                        &sorted[LEN - 1 - k],
+                        "{name}, {strategy}, k = {k}, reversed order"
+                    );
+                }
+            }
+        }
+
+        let median_of_three: Strategy = ("median_of_three", median_of_three, 16);
+        let ninther: Strategy = ("ninther", ninther, 5);
+        for (name, data) in patterns::<LEN>() {
+            // The first and last elements of an organ pipe are its two smallest ones, so
+            // median_of_three is only checked against the other patterns
+            let strategies: &[Strategy] = match name {
+                "organ pipe" => &[ninther],
+                _ => &[median_of_three, ninther],
+            };
+            check(name, data, strategies, ThreeWay);
+            check(name, data, strategies, Lomuto);
+            check(name, data, strategies, Hoare);
+            check(name, data, strategies, Block);
+        }
+    }
 }
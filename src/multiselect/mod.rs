@@ -0,0 +1,211 @@
+//! Selection of several ranks at once.
+
+use core::{cmp::Ordering, ops::Range};
+
+use not_empty::NonEmptySlice;
+
+use crate::{
+    check_rank, checked_pivot,
+    get_pivot::PivotStrategy,
+    partition::{PartitionScheme, ThreeWay},
+};
+
+/// Partitions an unsorted, non-empty slice around every rank in `ks` at once.
+///
+/// After the call, for every `k` in `ks`, the element at index `k` is the k-th smallest element,
+/// and `s` is partitioned around it as with [`quickselect`](crate::quickselect). Read the results
+/// back from `s`. The ranks do not need to be sorted nor distinct.
+///
+/// Each partition is shared by all the ranks that fall in the slice being partitioned, and only
+/// the parts that still contain a requested rank are partitioned further. This is cheaper than
+/// calling [`quickselect`](crate::quickselect) once per rank.
+///
+/// `get_pivot` receives the smallest rank that falls in the slice it is given. When both sides of
+/// a partition contain a rank, the smaller side is partitioned first and the larger side after it,
+/// so the slice given to `get_pivot` can be longer than the one of its previous call.
+///
+/// # Panics
+///
+/// Panics if any of the ranks in `ks` is out of bounds for the given slice `s`.
+/// Additionally, panics if the index returned by `get_pivot` is out of bounds
+/// for the input slice passed to it.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::{get_pivot::median_of_three, multiselect};
+///
+/// let mut arr: [u32; 1000] = core::array::from_fn(|i| (i as u32 * 7919) % 1000);
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let percentiles = [500, 900, 990, 999];
+/// multiselect(s, &percentiles, median_of_three);
+/// assert_eq!(percentiles.map(|k| arr[k]), [500, 900, 990, 999]);
+/// ```
+pub fn multiselect<T: Ord>(
+    s: &mut NonEmptySlice<T>,
+    ks: &[usize],
+    get_pivot: impl PivotStrategy<T>,
+) {
+    multiselect_by(s, ks, T::cmp, get_pivot)
+}
+
+/// [`multiselect`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`multiselect`].
+pub fn multiselect_by<T>(
+    s: &mut NonEmptySlice<T>,
+    ks: &[usize],
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+    get_pivot: impl PivotStrategy<T>,
+) {
+    for &k in ks {
+        check_rank(s, k);
+    }
+    select_ranks_by(s, ks, &mut compare, checked_pivot(get_pivot));
+}
+
+/// [`multiselect_by`] without checking the indices returned by `get_pivot`.
+///
+/// Ranks out of bounds are ignored.
+pub(crate) fn select_ranks_by<T, F>(
+    s: &mut NonEmptySlice<T>,
+    ks: &[usize],
+    compare: &mut F,
+    mut get_pivot: impl PivotStrategy<T>,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut iteration = 0;
+    multiselect_range(s, 0, ks, compare, &mut get_pivot, &mut iteration);
+}
+
+/// Partitions `s`, which starts at index `offset` of the original slice,
+/// around every rank of `ks` that falls within it.
+fn multiselect_range<T, F>(
+    mut s: &mut NonEmptySlice<T>,
+    mut offset: usize,
+    ks: &[usize],
+    compare: &mut F,
+    get_pivot: &mut impl PivotStrategy<T>,
+    iteration: &mut usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let contains = |range: Range<usize>| ks.iter().any(|k| range.contains(k));
+
+    loop {
+        let len = s.len().get();
+        let Some(k) = ks
+            .iter()
+            .copied()
+            .filter(|k| (offset..offset + len).contains(k))
+            .min()
+        else {
+            return;
+        };
+
+        let pivot_index = get_pivot.pivot_by(s, k - offset, *iteration, compare, &mut |_, _| {});
+        *iteration += 1;
+        let Range { start: lt, end: gt } =
+            ThreeWay.partition(s, pivot_index, compare, &mut |_, _| {});
+
+        let left = contains(offset..offset + lt);
+        let right = contains(offset + gt..offset + len);
+        let (before, rest) = s.split_at_mut(lt);
+        let after = &mut rest[gt - lt..];
+
+        // Recurse into the smaller side and loop on the larger one, to bound the recursion depth
+        // Safety: each side is only used when it contains a rank, so it is not empty
+        match (left, right) {
+            (true, true) if lt < len - gt => {
+                let before = unsafe { NonEmptySlice::new_mut_unchecked(before) };
+                multiselect_range(before, offset, ks, compare, get_pivot, iteration);
+                s = unsafe { NonEmptySlice::new_mut_unchecked(after) };
+                offset += gt;
+            }
+            (true, true) => {
+                let after = unsafe { NonEmptySlice::new_mut_unchecked(after) };
+                multiselect_range(after, offset + gt, ks, compare, get_pivot, iteration);
+                s = unsafe { NonEmptySlice::new_mut_unchecked(before) };
+            }
+            (true, false) => s = unsafe { NonEmptySlice::new_mut_unchecked(before) },
+            (false, true) => {
+                s = unsafe { NonEmptySlice::new_mut_unchecked(after) };
+                offset += gt;
+            }
+            (false, false) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_pivot::{first_index, middle_index};
+    use crate::test_data::{random, shuffled};
+
+    #[test]
+    fn partitions_every_rank() {
+        let data = random::<500>(1, 97).map(|x| x as u16);
+        let mut sorted = data;
+        sorted.sort_unstable();
+
+        let cases: [&[usize]; 5] = [
+            &[],
+            &[0],
+            &[499, 0],
+            &[250, 10, 250, 490, 10],
+            &[1, 2, 3, 4, 5],
+        ];
+        for ks in cases {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            multiselect(s, ks, middle_index);
+            for &k in ks {
+                assert_eq!(arr[k], sorted[k], "{ks:?}");
+                assert!(arr[..k].iter().all(|x| *x <= sorted[k]));
+                assert!(arr[k..].iter().all(|x| *x >= sorted[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn shares_partitions() {
+        let data = shuffled::<1000>(1);
+        let ks = [500, 900, 990, 999];
+
+        let mut separate = 0;
+        for k in ks {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            crate::quickselect_by(
+                s,
+                k,
+                |a, b| {
+                    separate += 1;
+                    a.cmp(b)
+                },
+                first_index,
+            );
+        }
+
+        let mut together = 0;
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        multiselect_by(
+            s,
+            &ks,
+            |a, b| {
+                together += 1;
+                a.cmp(b)
+            },
+            first_index,
+        );
+        assert_eq!(ks.map(|k| arr[k]), [500, 900, 990, 999]);
+        assert!(together < separate, "{together} >= {separate}");
+    }
+}
@@ -0,0 +1,427 @@
+//! Partition schemes used by the selection loop.
+//!
+//! Every iteration of [`quickselect`](crate::quickselect) partitions the remaining slice around
+//! the chosen pivot. The scheme decides how the elements are moved, which matters for the number
+//! of swaps, for branch prediction, and for inputs with many equal elements.
+//!
+//! | Scheme       | Elements equal to the pivot       | Notes                                     |
+//! |--------------|-----------------------------------|-------------------------------------------|
+//! | [`ThreeWay`] | grouped around the pivot          | never degrades on duplicates              |
+//! | [`Lomuto`]   | all sent left                     | quadratic when all elements are equal     |
+//! | [`Hoare`]    | spread over both sides            | fewer swaps than [`Lomuto`]               |
+//! | [`Block`]    | grouped when the pivot is minimum | the default, fastest on random data       |
+//!
+//! Use [`quickselect_with_scheme`](crate::quickselect_with_scheme) to select a scheme.
+//!
+//! Run `cargo bench --bench partition` to compare the schemes on random integers, integers with
+//! few distinct values and larger records.
+
+use core::{cmp::Ordering, ops::Range};
+
+use not_empty::NonEmptySlice;
+
+/// A way to partition a slice around a pivot.
+///
+/// # Safety
+///
+/// [`partition`](PartitionScheme::partition) MUST return a non-empty range within the bounds of
+/// the slice passed to it. The selection loop relies on it to slice without bounds checks.
+pub unsafe trait PartitionScheme {
+    /// Partitions `s` around the element at `pivot_index`, according to `compare`.
+    ///
+    /// Returns a range `lt..gt` containing the final position of the pivot, such that every
+    /// element in `s[..lt]` is less than or equal to the pivot, every element in `s[lt..gt]` is
+    /// equal to the pivot, and every element in `s[gt..]` is greater than or equal to the pivot.
+    ///
+    /// Every swap of two elements of `s` MUST be reported to `on_swap` with their indices in `s`,
+    /// in the order they are made. Co-permuting selection, such as
+    /// [`quickselect_with_swap`](crate::quickselect_with_swap), replays them on other slices.
+    ///
+    /// The selection loop always passes a `pivot_index` less than the length of `s`.
+    ///
+    /// # Panics
+    ///
+    /// The schemes of this module panic if `pivot_index` is out of bounds.
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize);
+}
+
+unsafe impl<P: PartitionScheme + ?Sized> PartitionScheme for &mut P {
+    #[inline]
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        (**self).partition(s, pivot_index, compare, on_swap)
+    }
+}
+
+/// Swaps `s[a]` and `s[b]` and reports the swap to `on_swap`.
+///
+/// # Safety
+///
+/// `a` and `b` MUST be less than the length of `s`.
+#[inline(always)]
+unsafe fn swap<T>(s: &mut [T], a: usize, b: usize, on_swap: &mut impl FnMut(usize, usize)) {
+    #[cfg(feature = "nightly")]
+    unsafe {
+        s.swap_unchecked(a, b)
+    };
+    #[cfg(not(feature = "nightly"))]
+    unsafe {
+        swap_unchecked(s, a, b)
+    };
+    on_swap(a, b);
+}
+
+/// Swaps `s[a]` and `s[b]` without bounds checks, like the unstable `slice::swap_unchecked`.
+///
+/// # Safety
+///
+/// `a` and `b` MUST be less than the length of `s`.
+#[cfg(not(feature = "nightly"))]
+#[inline(always)]
+unsafe fn swap_unchecked<T>(s: &mut [T], a: usize, b: usize) {
+    debug_assert!(a < s.len() && b < s.len());
+    let ptr = s.as_mut_ptr();
+    // Safety: both pointers are in bounds, and `ptr::swap` allows them to be equal
+    unsafe { core::ptr::swap(ptr.add(a), ptr.add(b)) };
+}
+
+/// Three-way partition following the
+/// [Dutch national flag](https://en.wikipedia.org/wiki/Dutch_national_flag_problem) scheme.
+///
+/// The elements are split into those less than, equal to, and greater than the pivot, in a single
+/// pass. Keeping the equal elements together lets the selection stop as soon as the rank falls
+/// among them, so slices with few distinct values do not degrade to quadratic time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ThreeWay;
+
+unsafe impl PartitionScheme for ThreeWay {
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        s.swap(0, pivot_index);
+        on_swap(0, pivot_index);
+
+        // s[..lt] < pivot, s[lt..i] == pivot, s[i..gt] not yet visited, s[gt..] > pivot
+        // The pivot itself moves around, but s[lt] is always equal to it since lt < i
+        let (mut lt, mut i, mut gt) = (0, 1, s.len().get());
+        while i < gt {
+            match compare(&s[i], &s[lt]) {
+                Ordering::Less => {
+                    unsafe { swap(s, lt, i, on_swap) };
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Equal => i += 1,
+                Ordering::Greater => {
+                    gt -= 1;
+                    unsafe { swap(s, i, gt, on_swap) };
+                }
+            }
+        }
+        lt..gt
+    }
+}
+
+/// Lomuto partition: a single forward scan that moves every element
+/// less than or equal to the pivot to the front.
+///
+/// This is the scheme the crate originally used. It does about one swap per element,
+/// and degrades to quadratic time when most elements are equal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Lomuto;
+
+unsafe impl PartitionScheme for Lomuto {
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        let last_index = s.len().get() - 1;
+        s.swap(pivot_index, last_index);
+        on_swap(pivot_index, last_index);
+
+        let mut i = 0;
+        for j in 0..last_index {
+            if compare(&s[j], &s[last_index]) != Ordering::Greater {
+                unsafe { swap(s, i, j, on_swap) };
+                i += 1;
+            }
+        }
+        unsafe { swap(s, i, last_index, on_swap) };
+        i..i + 1
+    }
+}
+
+/// Hoare partition: two scans moving towards each other,
+/// swapping the pairs of elements that are on the wrong side.
+///
+/// Both scans stop on elements equal to the pivot, which spreads them evenly over both sides.
+/// The pivot is not moved out of the way first, so sorted and reversed runs stay in order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Hoare;
+
+unsafe impl PartitionScheme for Hoare {
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        // The pivot is partitioned along with the other elements, p follows it around
+        let mut p = pivot_index;
+
+        // s[..i] <= pivot, s[j..] >= pivot
+        let (mut i, mut j) = (0, s.len().get());
+        loop {
+            while i < j && compare(&s[i], &s[p]) == Ordering::Less {
+                i += 1;
+            }
+            while i < j && compare(&s[j - 1], &s[p]) == Ordering::Greater {
+                j -= 1;
+            }
+            if i >= j {
+                break;
+            }
+            // s[i] >= pivot and s[j - 1] <= pivot
+            unsafe { swap(s, i, j - 1, on_swap) };
+            if p == i {
+                p = j - 1;
+            } else if p == j - 1 {
+                p = i;
+            }
+            i += 1;
+            j -= 1;
+        }
+
+        // s[i - 1] <= pivot when the pivot is on the left, s[i] >= pivot when it is on the right
+        let mid = if p < i { i - 1 } else { i };
+        unsafe { swap(s, p, mid, on_swap) };
+        mid..mid + 1
+    }
+}
+
+/// Number of elements scanned at once by [`Block`], small enough for the offsets to fit in a `u8`.
+const BLOCK: usize = 128;
+
+/// Branchless block partition, after
+/// [BlockQuicksort](https://arxiv.org/abs/1604.06697) by Edelkamp and Weiß.
+///
+/// Blocks at both ends of the slice are scanned first, recording the offsets of the misplaced
+/// elements in small buffers without branching on the comparisons. The misplaced elements are
+/// then swapped pairwise. This avoids most branch mispredictions on random data.
+///
+/// Elements equal to the pivot go right. When the pivot is the minimum of the slice, a second pass
+/// groups the elements equal to it, so that all-equal slices do not degrade to quadratic time.
+/// As with [`Hoare`], the pivot is not moved out of the way first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Block;
+
+unsafe impl PartitionScheme for Block {
+    fn partition<T, F, S>(
+        &mut self,
+        s: &mut NonEmptySlice<T>,
+        pivot_index: usize,
+        compare: &mut F,
+        on_swap: &mut S,
+    ) -> Range<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        S: FnMut(usize, usize),
+    {
+        // The pivot is partitioned along with the other elements, p follows it around
+        let mut p = pivot_index;
+
+        // s[..l] < pivot, s[r..] >= pivot
+        let (mut l, mut r) = (0, s.len().get());
+        let mut offsets_l = [0u8; BLOCK];
+        let mut offsets_r = [0u8; BLOCK];
+        let (mut start_l, mut num_l) = (0, 0);
+        let (mut start_r, mut num_r) = (0, 0);
+
+        while r - l >= 2 * BLOCK {
+            if num_l == 0 {
+                start_l = 0;
+                for i in 0..BLOCK {
+                    offsets_l[num_l] = i as u8;
+                    num_l += (compare(&s[l + i], &s[p]) != Ordering::Less) as usize;
+                }
+            }
+            if num_r == 0 {
+                start_r = 0;
+                for i in 0..BLOCK {
+                    offsets_r[num_r] = i as u8;
+                    num_r += (compare(&s[r - 1 - i], &s[p]) == Ordering::Less) as usize;
+                }
+            }
+
+            let num = num_l.min(num_r);
+            for j in 0..num {
+                let a = l + offsets_l[start_l + j] as usize;
+                let b = r - 1 - offsets_r[start_r + j] as usize;
+                unsafe { swap(s, a, b, on_swap) };
+                // Only the left block holds elements that are not less than the pivot
+                if a == p {
+                    p = b;
+                }
+            }
+            (start_l, num_l) = (start_l + num, num_l - num);
+            (start_r, num_r) = (start_r + num, num_r - num);
+
+            if num_l == 0 {
+                l += BLOCK;
+            }
+            if num_r == 0 {
+                r -= BLOCK;
+            }
+        }
+
+        // The remainder, including any block left half done, is partitioned element by element
+        let mut mid = l;
+        for j in l..r {
+            if compare(&s[j], &s[p]) == Ordering::Less {
+                unsafe { swap(s, mid, j, on_swap) };
+                if mid == p {
+                    p = j;
+                }
+                mid += 1;
+            }
+        }
+
+        // The pivot is not less than itself, so p >= mid
+        unsafe { swap(s, p, mid, on_swap) };
+        if mid > 0 {
+            return mid..mid + 1;
+        }
+
+        // The pivot is the minimum, so group the elements equal to it
+        let mut gt = 1;
+        for j in 1..s.len().get() {
+            if compare(&s[j], &s[0]) != Ordering::Greater {
+                unsafe { swap(s, gt, j, on_swap) };
+                gt += 1;
+            }
+        }
+        0..gt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_pivot::RandomPivot;
+
+    const LEN: usize = 1000;
+
+    fn check(mut scheme: impl PartitionScheme) {
+        let mut random = RandomPivot::new(1);
+
+        for modulo in [u64::MAX, 100, 3, 1] {
+            for len in [1, 2, 3, 10, 255, 256, 257, 300, LEN] {
+                let mut arr = [0; LEN];
+                arr.iter_mut().for_each(|x| *x = random.next_u64() % modulo);
+                let pivot_index = random.below(len);
+                let pivot = arr[pivot_index];
+
+                let original = arr;
+                let mut rows: [usize; LEN] = core::array::from_fn(|i| i);
+
+                let s = NonEmptySlice::new_mut(&mut arr[..len]).unwrap();
+                let Range { start, end } =
+                    scheme.partition(s, pivot_index, &mut u64::cmp, &mut |a, b| rows.swap(a, b));
+                assert!(start < end && end <= len, "{start}..{end}, len = {len}");
+                assert!((0..len).all(|i| arr[i] == original[rows[i]]));
+                assert!(arr[..start].iter().all(|&x| x <= pivot));
+                assert!(arr[start..end].iter().all(|&x| x == pivot));
+                assert!(arr[end..len].iter().all(|&x| x >= pivot));
+            }
+        }
+    }
+
+    #[test]
+    fn swap_matches_slice_swap() {
+        let arr: [u32; 8] = core::array::from_fn(|i| i as u32);
+        for a in 0..arr.len() {
+            for b in 0..arr.len() {
+                let (mut expected, mut actual) = (arr, arr);
+                expected.swap(a, b);
+                let mut swaps = 0;
+                unsafe { swap(&mut actual, a, b, &mut |_, _| swaps += 1) };
+                assert_eq!(actual, expected);
+                assert_eq!(swaps, 1);
+            }
+        }
+    }
+
+    /// The exact arrangement left by every scheme, which must not depend on the `nightly` feature.
+    #[test]
+    fn arrangements() {
+        fn arrangement(mut scheme: impl PartitionScheme) -> [u32; 10] {
+            let mut arr = [7, 3, 9, 1, 4, 8, 6, 2, 3, 5];
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(scheme.partition(s, 4, &mut u32::cmp, &mut |_, _| {}), 4..5);
+            arr
+        }
+
+        assert_eq!(arrangement(ThreeWay), [3, 3, 1, 2, 4, 6, 8, 7, 5, 9]);
+        assert_eq!(arrangement(Lomuto), [3, 1, 2, 3, 4, 8, 6, 9, 7, 5]);
+        assert_eq!(arrangement(Hoare), [3, 3, 2, 1, 4, 8, 6, 9, 7, 5]);
+        assert_eq!(arrangement(Block), [3, 1, 2, 3, 4, 8, 6, 9, 7, 5]);
+    }
+
+    #[test]
+    fn three_way() {
+        check(ThreeWay);
+    }
+
+    #[test]
+    fn lomuto() {
+        check(Lomuto);
+    }
+
+    #[test]
+    fn hoare() {
+        check(Hoare);
+    }
+
+    #[test]
+    fn block() {
+        check(Block);
+    }
+}
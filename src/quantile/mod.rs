@@ -0,0 +1,562 @@
+//! Quantiles of numeric data.
+//!
+//! [`quantile`] converts a probability `q` in `[0, 1]` to ranks, selects them
+//! with [`introselect`](crate::introselect), and combines them according to a [`QuantileMethod`].
+//! [`median`], [`median_low`] and [`median_high`] cover the most common case.
+//!
+//! NaN has no rank, so it is never ordered among the other numbers: [`quantile`] returns
+//! [`QuantileError::Nan`], and the medians return NaN, like arithmetic does.
+//!
+//! # Examples
+//!
+//! ```
+//! use not_empty::NonEmptySlice;
+//! use alg_quickselect::quantile::{quantile, QuantileMethod};
+//!
+//! let mut arr = [7, 1, 4, 2, 9, 3];
+//! let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+//!
+//! assert_eq!(quantile(s, 0.5, QuantileMethod::Linear), Ok(3.5));
+//! assert_eq!(quantile(s, 0.5, QuantileMethod::Lower), Ok(3.0));
+//! ```
+
+use core::{cmp::Ordering, fmt};
+
+use not_empty::NonEmptySlice;
+
+use crate::introselect_by;
+
+/// Numeric types that quantiles can be computed for.
+///
+/// Implemented for all primitive integer and floating-point types.
+pub trait Numeric: Copy {
+    /// Total ordering of the values.
+    ///
+    /// For floating-point types, this is [`f64::total_cmp`]: NaN values are ordered
+    /// after positive infinity, or before negative infinity if their sign bit is set.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Returns `true` if the value is NaN, which is never the case for integer types.
+    #[inline]
+    fn is_nan(self) -> bool {
+        false
+    }
+
+    /// Converts the value to [`f64`], rounding to the nearest representable value if needed.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_numeric_int {
+    ($($t:ty)*) => {$(
+        impl Numeric for $t {
+            #[inline]
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )*};
+}
+impl_numeric_int!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+macro_rules! impl_numeric_float {
+    ($($t:ty)*) => {$(
+        impl Numeric for $t {
+            #[inline]
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                <$t>::total_cmp(self, other)
+            }
+            #[inline]
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )*};
+}
+impl_numeric_float!(f32 f64);
+
+/// How to compute a quantile that falls between two elements.
+///
+/// These are the nine definitions of
+/// [Hyndman and Fan (1996)](https://doi.org/10.2307/2684934), numbered `1` to `9` below,
+/// plus four variants of [`Linear`](QuantileMethod::Linear). Names follow
+/// [NumPy](https://numpy.org/doc/stable/reference/generated/numpy.quantile.html).
+///
+/// The descriptions use `n` for the length of the slice, `x[i]` for its `i`-th smallest
+/// element counting from 0, and `h` for the fractional rank the quantile corresponds to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QuantileMethod {
+    /// `1`: inverse of the empirical distribution function, `x[ceil(n q) - 1]`.
+    InvertedCdf,
+    /// `2`: like [`InvertedCdf`](QuantileMethod::InvertedCdf),
+    /// but averages the two elements at discontinuities.
+    AveragedInvertedCdf,
+    /// `3`: the element closest to `n q - 1/2`, the even one on ties (SAS definition).
+    ClosestObservation,
+    /// `4`: linear interpolation of the empirical distribution function, `h = n q - 1`.
+    InterpolatedInvertedCdf,
+    /// `5`: piecewise linear with the nodes at the midpoints of the steps, `h = n q - 1/2`.
+    Hazen,
+    /// `6`: linear interpolation of the expectations of the order statistics of the uniform
+    /// distribution, `h = (n + 1) q - 1`.
+    Weibull,
+    /// `7`: linear interpolation of the modes of the order statistics of the uniform
+    /// distribution, `h = (n - 1) q`. This is the default of NumPy, R and Excel.
+    #[default]
+    Linear,
+    /// `8`: approximately median-unbiased regardless of the distribution,
+    /// `h = (n + 1/3) q - 2/3`.
+    MedianUnbiased,
+    /// `9`: approximately unbiased for normally distributed data,
+    /// `h = (n + 1/4) q - 5/8`.
+    NormalUnbiased,
+    /// `x[floor(h)]` with `h` from [`Linear`](QuantileMethod::Linear).
+    Lower,
+    /// `x[ceil(h)]` with `h` from [`Linear`](QuantileMethod::Linear).
+    Higher,
+    /// `x[round(h)]` with `h` from [`Linear`](QuantileMethod::Linear), the even one on ties.
+    Nearest,
+    /// Average of [`Lower`](QuantileMethod::Lower) and [`Higher`](QuantileMethod::Higher).
+    Midpoint,
+}
+
+/// Error returned by [`quantile`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuantileError {
+    /// The probability is NaN or outside of `[0, 1]`.
+    OutOfRange {
+        /// The requested probability.
+        q: f64,
+    },
+    /// There are no elements to compute the quantile of.
+    ///
+    /// Only returned by inputs that can be empty.
+    #[cfg_attr(
+        feature = "std",
+        doc = "For example, [`IteratorExt::quantile`](crate::IteratorExt::quantile)."
+    )]
+    Empty,
+    /// The input contains NaN.
+    Nan {
+        /// Index of the first NaN found.
+        index: usize,
+    },
+}
+
+impl fmt::Display for QuantileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange { q } => write!(f, "quantile out of range: {q} is not in [0, 1]"),
+            Self::Empty => f.write_str("quantile of an empty input"),
+            Self::Nan { index } => write!(f, "quantile of an input with NaN at index {index}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QuantileError {}
+
+/// How the quantile is computed from the slice, once `q` and the method are known.
+enum Estimate {
+    /// `x[i]`
+    At(usize),
+    /// `x[i] + g (x[i + 1] - x[i])`, with `0 <= g < 1`
+    Between(usize, f64),
+}
+
+/// Computes the `q`-th quantile of an unsorted, non-empty slice.
+///
+/// The elements are converted to [`f64`] with [`Numeric::to_f64`]. Interpolating methods need
+/// two neighbouring ranks: the second one is found as the minimum of the right partition left by
+/// the first selection, not with a second selection.
+///
+/// After the call, the slice is partitioned around the lower of the selected ranks,
+/// like with [`quickselect`](crate::quickselect).
+///
+/// # Errors
+///
+/// Returns [`QuantileError::OutOfRange`] if `q` is NaN or outside of `[0, 1]`,
+/// and [`QuantileError::Nan`] if the slice contains NaN. The slice is left unchanged in both
+/// cases.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quantile::{quantile, QuantileError, QuantileMethod};
+///
+/// let mut arr = [1.0, 2.0, 3.0, 4.0];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(quantile(s, 0.25, QuantileMethod::Linear), Ok(1.75));
+/// assert_eq!(quantile(s, 0.25, QuantileMethod::Weibull), Ok(1.25));
+/// assert!(quantile(s, 1.5, QuantileMethod::Linear).is_err());
+///
+/// let mut arr = [1.0, f64::NAN, 3.0];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(
+///     quantile(s, 0.5, QuantileMethod::Linear),
+///     Err(QuantileError::Nan { index: 1 })
+/// );
+/// ```
+pub fn quantile<T: Numeric>(
+    s: &mut NonEmptySlice<T>,
+    q: f64,
+    method: QuantileMethod,
+) -> Result<f64, QuantileError> {
+    if !(0.0..=1.0).contains(&q) {
+        return Err(QuantileError::OutOfRange { q });
+    }
+    if let Some(index) = find_nan(s) {
+        return Err(QuantileError::Nan { index });
+    }
+
+    let n = s.len().get();
+    let value = match estimate(n, q, method) {
+        Estimate::At(i) => introselect_by(s, i, T::total_cmp).to_f64(),
+        Estimate::Between(i, g) => {
+            let (low, high) = select_pair(s, i);
+            let (low, high) = (low.to_f64(), high.to_f64());
+            if g == 0.0 {
+                low
+            } else if g == 0.5 {
+                midpoint(low, high)
+            } else {
+                low + g * (high - low)
+            }
+        }
+    };
+    Ok(value)
+}
+
+/// Returns the lower median of an unsorted, non-empty slice.
+///
+/// For an odd length, this is the middle element. For an even length,
+/// this is the smaller of the two middle elements.
+///
+/// After the call, the slice is partitioned around the median,
+/// like with [`quickselect`](crate::quickselect).
+/// If the slice contains NaN, the first one is returned and the slice is left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quantile::median_low;
+///
+/// let mut arr = [4, 1, 3, 2];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(median_low(s), 2);
+/// ```
+pub fn median_low<T: Numeric>(s: &mut NonEmptySlice<T>) -> T {
+    if let Some(index) = find_nan(s) {
+        return s[index];
+    }
+    let k = (s.len().get() - 1) / 2;
+    *introselect_by(s, k, T::total_cmp)
+}
+
+/// Returns the upper median of an unsorted, non-empty slice.
+///
+/// For an odd length, this is the middle element. For an even length,
+/// this is the larger of the two middle elements.
+///
+/// After the call, the slice is partitioned around the median,
+/// like with [`quickselect`](crate::quickselect).
+/// If the slice contains NaN, the first one is returned and the slice is left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quantile::median_high;
+///
+/// let mut arr = [4, 1, 3, 2];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(median_high(s), 3);
+/// ```
+pub fn median_high<T: Numeric>(s: &mut NonEmptySlice<T>) -> T {
+    if let Some(index) = find_nan(s) {
+        return s[index];
+    }
+    let k = s.len().get() / 2;
+    *introselect_by(s, k, T::total_cmp)
+}
+
+/// Returns the median of an unsorted, non-empty slice.
+///
+/// For an odd length, this is the middle element. For an even length,
+/// this is the average of the two middle elements. The second one is found as the minimum of the
+/// right partition left by the selection of the first one, not with a second selection.
+///
+/// Same as [`quantile`] at `0.5` with [`QuantileMethod::Linear`].
+///
+/// After the call, the slice is partitioned around the lower median,
+/// like with [`quickselect`](crate::quickselect).
+/// If the slice contains NaN, NaN is returned and the slice is left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::quantile::median;
+///
+/// let mut arr = [4, 1, 3, 2];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(median(s), 2.5);
+/// ```
+pub fn median<T: Numeric>(s: &mut NonEmptySlice<T>) -> f64 {
+    if let Some(index) = find_nan(s) {
+        return s[index].to_f64();
+    }
+    let len = s.len().get();
+    if len % 2 == 1 {
+        return introselect_by(s, len / 2, T::total_cmp).to_f64();
+    }
+    let (low, high) = select_pair(s, len / 2 - 1);
+    midpoint(low.to_f64(), high.to_f64())
+}
+
+/// Returns the index of the first NaN in `s`.
+#[inline]
+pub(crate) fn find_nan<T: Numeric>(s: &[T]) -> Option<usize> {
+    s.iter().position(|x| x.is_nan())
+}
+
+/// Selects the `i`-th and `i + 1`-th smallest elements, or the `i`-th smallest twice if it is
+/// the largest.
+///
+/// The slice is partitioned around index `i`, so the `i + 1`-th smallest element is the minimum
+/// of the elements after it.
+pub(crate) fn select_pair<T: Numeric>(s: &mut NonEmptySlice<T>, i: usize) -> (T, T) {
+    let low = *introselect_by(s, i, T::total_cmp);
+    let high = s[i + 1..]
+        .iter()
+        .copied()
+        .min_by(T::total_cmp)
+        .unwrap_or(low);
+    (low, high)
+}
+
+/// Average of two numbers, without overflowing to infinity.
+#[inline]
+pub(crate) fn midpoint(a: f64, b: f64) -> f64 {
+    a / 2.0 + b / 2.0
+}
+
+/// Maps `q` to the ranks that make up the quantile in a slice of length `n`.
+fn estimate(n: usize, q: f64, method: QuantileMethod) -> Estimate {
+    use QuantileMethod::*;
+
+    let last = n - 1;
+    let nq = n as f64 * q;
+    // For h >= 0, truncation rounds down
+    let floor = |h: f64| (h.max(0.0) as usize).min(last);
+    let ceil = |h: f64| {
+        let i = floor(h);
+        if (i as f64) < h {
+            (i + 1).min(last)
+        } else {
+            i
+        }
+    };
+    // Continuous methods, with the plotting positions of Hyndman and Fan
+    let between = |alpha: f64, beta: f64| {
+        let h = (nq + alpha + q * (1.0 - alpha - beta) - 1.0).clamp(0.0, last as f64);
+        let i = floor(h);
+        Estimate::Between(i, h - i as f64)
+    };
+
+    match method {
+        InvertedCdf => Estimate::At(ceil(nq - 1.0)),
+        AveragedInvertedCdf => {
+            let j = floor(nq);
+            if j as f64 == nq && j > 0 && j < n {
+                Estimate::Between(j - 1, 0.5)
+            } else {
+                Estimate::At(ceil(nq - 1.0))
+            }
+        }
+        ClosestObservation => {
+            let h = nq - 0.5;
+            if h < 0.0 {
+                return Estimate::At(0);
+            }
+            let j = h as usize;
+            // Ranks counted from 1: x_j on exact even ranks, x_(j + 1) otherwise
+            let rank = if j as f64 == h && j % 2 == 0 {
+                j
+            } else {
+                j + 1
+            };
+            Estimate::At(rank.saturating_sub(1).min(last))
+        }
+        InterpolatedInvertedCdf => between(0.0, 1.0),
+        Hazen => between(0.5, 0.5),
+        Weibull => between(0.0, 0.0),
+        Linear => between(1.0, 1.0),
+        MedianUnbiased => between(1.0 / 3.0, 1.0 / 3.0),
+        NormalUnbiased => between(3.0 / 8.0, 3.0 / 8.0),
+        Lower => Estimate::At(floor(last as f64 * q)),
+        Higher => Estimate::At(ceil(last as f64 * q)),
+        Nearest => {
+            let h = last as f64 * q;
+            let i = floor(h);
+            let g = h - i as f64;
+            if g > 0.5 || (g == 0.5 && i % 2 == 1) {
+                Estimate::At(i + 1)
+            } else {
+                Estimate::At(i)
+            }
+        }
+        Midpoint => {
+            let h = last as f64 * q;
+            let i = floor(h);
+            if (i as f64) < h {
+                Estimate::Between(i, 0.5)
+            } else {
+                Estimate::At(i)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use QuantileMethod::*;
+
+    const METHODS: [QuantileMethod; 13] = [
+        InvertedCdf,
+        AveragedInvertedCdf,
+        ClosestObservation,
+        InterpolatedInvertedCdf,
+        Hazen,
+        Weibull,
+        Linear,
+        MedianUnbiased,
+        NormalUnbiased,
+        Lower,
+        Higher,
+        Nearest,
+        Midpoint,
+    ];
+
+    fn check(data: &[i32], q: f64, expected: [f64; 13]) {
+        for (method, expected) in METHODS.into_iter().zip(expected) {
+            let mut arr = [0; 16];
+            let arr = &mut arr[..data.len()];
+            arr.copy_from_slice(data);
+            // Reverse to make sure the input order does not matter
+            arr.reverse();
+            let s = NonEmptySlice::new_mut(arr).unwrap();
+            let result = quantile(s, q, method).unwrap();
+            assert!(
+                (result - expected).abs() < 1e-9,
+                "{method:?}, q = {q}: {result} != {expected}"
+            );
+        }
+    }
+
+    // Expected values of numpy.quantile
+    #[test]
+    fn matches_numpy() {
+        let data = [1, 2, 3, 4];
+        #[rustfmt::skip]
+        check(&data, 0.4, [2.0, 2.0, 2.0, 1.6, 2.1, 2.0, 2.2, 2.066666666666667, 2.075, 2.0, 3.0, 2.0, 2.5]);
+        #[rustfmt::skip]
+        check(&data, 0.5, [2.0, 2.5, 2.0, 2.0, 2.5, 2.5, 2.5, 2.5, 2.5, 2.0, 3.0, 3.0, 2.5]);
+        #[rustfmt::skip]
+        check(&data, 0.0, [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        #[rustfmt::skip]
+        check(&data, 1.0, [4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0, 4.0]);
+
+        let data = [10, 3, 7, 1, 12, 5, 8];
+        #[rustfmt::skip]
+        check(&data, 0.9, [12.0, 12.0, 10.0, 10.6, 11.6, 12.0, 10.8, 11.86666666666667, 11.8, 10.0, 12.0, 10.0, 11.0]);
+        #[rustfmt::skip]
+        check(&data, 0.1, [1.0, 1.0, 1.0, 1.0, 1.4, 1.0, 2.2, 1.133333333333333, 1.2, 1.0, 3.0, 3.0, 2.0]);
+    }
+
+    #[test]
+    fn medians() {
+        let mut arr = [5, 3, 9, 1, 7];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!((median_low(s), median_high(s), median(s)), (5, 5, 5.0));
+
+        let mut arr = [8, -2, 6, 0, 4, 10];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!((median_low(s), median_high(s), median(s)), (4, 6, 5.0));
+
+        let mut arr = [i64::MAX, i64::MAX - 2];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(median(s), i64::MAX as f64);
+
+        let mut arr = [f64::MAX, f64::MAX];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(median(s), f64::MAX);
+
+        for len in 1..=9 {
+            let mut arr: [u32; 9] = core::array::from_fn(|i| (9 - i) as u32 * 10);
+            let s = NonEmptySlice::new_mut(&mut arr[..len]).unwrap();
+            let expected = quantile(s, 0.5, Linear).unwrap();
+            assert_eq!(median(s), expected, "len = {len}");
+        }
+    }
+
+    #[test]
+    fn single_element() {
+        for method in METHODS {
+            for q in [0.0, 0.3, 1.0] {
+                let mut arr = [42u8];
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                assert_eq!(quantile(s, q, method), Ok(42.0));
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut arr = [1.0f32, 2.0];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        for q in [-0.1, 1.1] {
+            assert_eq!(quantile(s, q, Linear), Err(QuantileError::OutOfRange { q }));
+        }
+        assert!(quantile(s, f64::NAN, Linear).is_err());
+    }
+
+    #[test]
+    fn nan() {
+        let data = [2.0, f64::NAN, 1.0, -f64::NAN];
+        for method in METHODS {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(
+                quantile(s, 0.5, method),
+                Err(QuantileError::Nan { index: 1 })
+            );
+        }
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert!(median(s).is_nan());
+        assert!(median_low(s).is_nan());
+        assert!(median_high(s).is_nan());
+        assert_eq!(arr.map(f64::to_bits), data.map(f64::to_bits));
+
+        let mut arr = [3.0f32, 1.0, 2.0];
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(median(s), 2.0);
+    }
+}
@@ -0,0 +1,178 @@
+//! Selection methods on plain slices.
+
+use core::cmp::Ordering;
+
+use not_empty::NonEmptySlice;
+
+use crate::{
+    get_pivot::PivotStrategy, introselect, introselect_by, try_quickselect_with_strategy,
+    SelectError,
+};
+
+/// Selection methods for slices that may be empty.
+///
+/// The methods check that the slice is not empty and that the rank is in bounds, then call the
+/// corresponding [`NonEmptySlice`] function. They are also available on [`Vec`] through
+/// auto-deref.
+///
+/// [`Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
+///
+/// # Examples
+///
+/// ```
+/// use alg_quickselect::{get_pivot::median_of_three, SelectError, SliceExt};
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// assert_eq!(arr.quickselect(2), Some(&mut 3));
+/// assert_eq!(arr.quickselect_with(4, median_of_three), Ok(&mut 5));
+/// assert_eq!(arr.quickselect_with(0, |s| s.len().get() / 2), Ok(&mut 1));
+///
+/// let empty: &mut [u32] = &mut [];
+/// assert_eq!(empty.quickselect(0), None);
+/// assert_eq!(
+///     empty.quickselect_with(0, median_of_three),
+///     Err(SelectError::RankOutOfBounds { len: 0, k: 0 })
+/// );
+/// ```
+pub trait SliceExt<T> {
+    /// Finds the k-th smallest element with [`introselect`](crate::introselect),
+    /// or returns [`None`] if `k` is out of bounds.
+    fn quickselect(&mut self, k: usize) -> Option<&mut T>
+    where
+        T: Ord;
+
+    /// [`quickselect`](SliceExt::quickselect) with a comparator function,
+    /// see [`quickselect_by`](crate::quickselect_by).
+    fn quickselect_by(
+        &mut self,
+        k: usize,
+        compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Option<&mut T>;
+
+    /// Finds the k-th smallest element with the given pivot strategy,
+    /// see [`try_quickselect`](crate::try_quickselect).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SelectError::RankOutOfBounds`] if `k` is out of bounds, including for an empty
+    /// slice, and [`SelectError::InvalidPivot`] if `get_pivot` returns an index out of bounds.
+    fn quickselect_with(
+        &mut self,
+        k: usize,
+        get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+    ) -> Result<&mut T, SelectError>
+    where
+        T: Ord;
+
+    /// [`quickselect_with`](SliceExt::quickselect_with) with a [`PivotStrategy`],
+    /// see [`try_quickselect_with_strategy`](crate::try_quickselect_with_strategy).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`quickselect_with`](SliceExt::quickselect_with).
+    fn quickselect_with_strategy(
+        &mut self,
+        k: usize,
+        get_pivot: impl PivotStrategy<T>,
+    ) -> Result<&mut T, SelectError>
+    where
+        T: Ord;
+}
+
+impl<T> SliceExt<T> for [T] {
+    fn quickselect(&mut self, k: usize) -> Option<&mut T>
+    where
+        T: Ord,
+    {
+        if k >= self.len() {
+            return None;
+        }
+        NonEmptySlice::new_mut(self).ok().map(|s| introselect(s, k))
+    }
+
+    fn quickselect_by(
+        &mut self,
+        k: usize,
+        compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Option<&mut T> {
+        if k >= self.len() {
+            return None;
+        }
+        NonEmptySlice::new_mut(self)
+            .ok()
+            .map(|s| introselect_by(s, k, compare))
+    }
+
+    fn quickselect_with(
+        &mut self,
+        k: usize,
+        get_pivot: impl FnMut(&mut NonEmptySlice<T>) -> usize,
+    ) -> Result<&mut T, SelectError>
+    where
+        T: Ord,
+    {
+        self.quickselect_with_strategy(k, get_pivot)
+    }
+
+    fn quickselect_with_strategy(
+        &mut self,
+        k: usize,
+        get_pivot: impl PivotStrategy<T>,
+    ) -> Result<&mut T, SelectError>
+    where
+        T: Ord,
+    {
+        let len = self.len();
+        let s =
+            NonEmptySlice::new_mut(self).map_err(|_| SelectError::RankOutOfBounds { len, k })?;
+        try_quickselect_with_strategy(s, k, get_pivot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_pivot::{middle_index, MedianOfThree};
+    use crate::test_data::random;
+
+    #[test]
+    fn matches_sort() {
+        let data = random::<50>(1, 20);
+        let mut sorted = data;
+        sorted.sort_unstable();
+
+        for k in 0..data.len() {
+            let mut arr = data;
+            assert_eq!(arr.quickselect(k), Some(&mut sorted[k]));
+            let mut arr = data;
+            assert_eq!(arr.quickselect_with(k, middle_index), Ok(&mut sorted[k]));
+            let mut arr = data;
+            assert_eq!(
+                arr.quickselect_with_strategy(k, MedianOfThree),
+                Ok(&mut sorted[k])
+            );
+            let mut arr = data;
+            assert_eq!(
+                arr.quickselect_by(k, |a, b| b.cmp(a)).copied(),
+                Some(sorted[data.len() - 1 - k])
+            );
+        }
+
+        let mut arr = data;
+        assert_eq!(arr.quickselect(50), None);
+        assert_eq!(
+            arr.quickselect_with(50, middle_index),
+            Err(SelectError::RankOutOfBounds { len: 50, k: 50 })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn vec() {
+        let mut v = std::vec![3, 1, 2];
+        assert_eq!(v.quickselect(0), Some(&mut 1));
+
+        let mut empty = std::vec::Vec::<u32>::new();
+        assert_eq!(empty.quickselect_by(0, u32::cmp), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Instrumentation of the selection loop.
+//!
+//! An [`Observer`] is notified of the comparisons, swaps, pivots and partitions made by
+//! [`quickselect_with_observer`](crate::quickselect_with_observer). Every method does nothing by
+//! default, and the other selection functions use the `()` observer, which compiles away.
+//!
+//! [`SelectStats`] is an observer that counts the events, returned by
+//! [`quickselect_with_stats`](crate::quickselect_with_stats) to compare pivot strategies on a
+//! given workload:
+//!
+//! ```
+//! use not_empty::NonEmptySlice;
+//! use alg_quickselect::{get_pivot, quickselect_with_stats};
+//!
+//! let data: [u32; 1000] = core::array::from_fn(|i| i as u32);
+//!
+//! let mut arr = data;
+//! let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+//! let (_, first) = quickselect_with_stats(s, 500, get_pivot::first_index);
+//!
+//! let mut arr = data;
+//! let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+//! let (_, middle) = quickselect_with_stats(s, 500, get_pivot::middle_index);
+//!
+//! // Always picking the first element is quadratic on sorted input
+//! assert!(first.comparisons > 10 * middle.comparisons);
+//! ```
+
+use core::ops::Range;
+
+/// Receives the events of the selection loop.
+///
+/// Indices are in the slice passed to the selection function.
+pub trait Observer {
+    /// Two elements were compared.
+    ///
+    /// Only comparisons made with the comparator of the selection function are seen. The pivot
+    /// strategy receives it through [`PivotStrategy::pivot_by`], which the strategy types of the
+    /// [`get_pivot`](crate::get_pivot) module implement, such as [`MedianOfThree`]. Functions and
+    /// closures like [`median_of_three`] compare elements on their own, and are not seen.
+    ///
+    /// [`PivotStrategy::pivot_by`]: crate::get_pivot::PivotStrategy::pivot_by
+    /// [`MedianOfThree`]: crate::get_pivot::MedianOfThree
+    /// [`median_of_three`]: crate::get_pivot::median_of_three
+    #[inline(always)]
+    fn compare(&mut self) {}
+
+    /// The elements at `a` and `b` were swapped, by a partition or by the pivot strategy.
+    ///
+    /// Like comparisons, the swaps of a pivot strategy are only seen if it reports them in
+    /// [`PivotStrategy::pivot_by`], as [`MedianOfMedians`] does.
+    ///
+    /// [`PivotStrategy::pivot_by`]: crate::get_pivot::PivotStrategy::pivot_by
+    /// [`MedianOfMedians`]: crate::get_pivot::MedianOfMedians
+    #[inline(always)]
+    fn swap(&mut self, a: usize, b: usize) {
+        let _ = (a, b);
+    }
+
+    /// The pivot strategy chose the element at `index`.
+    #[inline(always)]
+    fn pivot(&mut self, index: usize) {
+        let _ = index;
+    }
+
+    /// The subslice at `range` was partitioned, and the elements equal to the pivot ended up at
+    /// `equal`. The selection continues in the part of `range` that contains the rank,
+    /// or stops if `equal` contains it.
+    #[inline(always)]
+    fn partition(&mut self, range: Range<usize>, equal: Range<usize>) {
+        let _ = (range, equal);
+    }
+}
+
+impl Observer for () {}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    #[inline(always)]
+    fn compare(&mut self) {
+        (**self).compare();
+    }
+
+    #[inline(always)]
+    fn swap(&mut self, a: usize, b: usize) {
+        (**self).swap(a, b);
+    }
+
+    #[inline(always)]
+    fn pivot(&mut self, index: usize) {
+        (**self).pivot(index);
+    }
+
+    #[inline(always)]
+    fn partition(&mut self, range: Range<usize>, equal: Range<usize>) {
+        (**self).partition(range, equal);
+    }
+}
+
+/// Counts of the events of a selection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SelectStats {
+    /// Number of comparisons, including those of the pivot strategy when it uses the
+    /// comparator of the selection, see [`Observer::compare`].
+    pub comparisons: usize,
+    /// Number of swaps, see [`Observer::swap`].
+    pub swaps: usize,
+    /// Number of calls to the pivot strategy.
+    pub pivot_calls: usize,
+    /// Number of partition rounds.
+    pub partitions: usize,
+    /// Total length of the subslices partitioned.
+    ///
+    /// This is about twice the length of the slice when every partition halves the remaining
+    /// subslice, and grows quadratically when each one only removes a few elements.
+    pub elements_partitioned: usize,
+}
+
+impl Observer for SelectStats {
+    #[inline]
+    fn compare(&mut self) {
+        self.comparisons += 1;
+    }
+
+    #[inline]
+    fn swap(&mut self, _: usize, _: usize) {
+        self.swaps += 1;
+    }
+
+    #[inline]
+    fn pivot(&mut self, _: usize) {
+        self.pivot_calls += 1;
+    }
+
+    #[inline]
+    fn partition(&mut self, range: Range<usize>, _: Range<usize>) {
+        self.partitions += 1;
+        self.elements_partitioned += range.len();
+    }
+}
@@ -0,0 +1,40 @@
+//! Inputs shared by the unit tests of the crate.
+
+use crate::get_pivot::RandomPivot;
+
+/// `N` pseudo-random values less than `modulo`. The same seed always yields the same values.
+pub(crate) fn random<const N: usize>(seed: u64, modulo: u32) -> [u32; N] {
+    let mut random = RandomPivot::new(seed);
+    core::array::from_fn(|_| (random.next_u64() % modulo as u64) as u32)
+}
+
+/// A pseudo-random permutation of `0..N`, shuffled with the Fisher-Yates algorithm.
+pub(crate) fn shuffled<const N: usize>(seed: u64) -> [u32; N] {
+    let mut random = RandomPivot::new(seed);
+    let mut data = core::array::from_fn(|i| i as u32);
+    for i in (1..N).rev() {
+        data.swap(i, random.below(i + 1));
+    }
+    data
+}
+
+/// Permutations of `0..N` in the orders that trip up the simple pivot strategies,
+/// along with a [`shuffled`] one.
+///
+/// `N` must be a multiple of 10, the number of teeth of the sawtooth.
+pub(crate) fn patterns<const N: usize>() -> [(&'static str, [u32; N]); 5] {
+    assert_eq!(N % 10, 0, "N must be a multiple of 10");
+    let (last, half, tooth) = (N as u32 - 1, N as u32 / 2, N as u32 / 10);
+
+    let sorted = core::array::from_fn(|i| i as u32);
+    let reversed = sorted.map(|i| last - i);
+    let organ_pipe = sorted.map(|i| if i < half { 2 * i } else { 2 * (last - i) + 1 });
+    let sawtooth = sorted.map(|i| (i % tooth) * 10 + i / tooth);
+    [
+        ("sorted", sorted),
+        ("reversed", reversed),
+        ("organ pipe", organ_pipe),
+        ("sawtooth", sawtooth),
+        ("shuffled", shuffled(1)),
+    ]
+}
@@ -0,0 +1,282 @@
+//! Worst-case inputs for pivot strategies.
+//!
+//! This module implements the adversary of McIlroy's
+//! [A Killer Adversary for Quicksort](https://www.cs.dartmouth.edu/~doug/mdmspe.pdf), adapted to
+//! selection. The elements start as *gas*, with no value yet, and greater than every element with
+//! a value. When two gas elements are compared, one of them is frozen to the next smallest value,
+//! preferring the one most likely to be the pivot: the last gas element compared to a frozen one.
+//!
+//! The pivots end up among the smallest elements, so each partition only removes a few of them,
+//! and the selection keeps going on the gas. Once it returns, the values given to the elements are
+//! an input on which the strategy makes the same choices, and the same number of comparisons.
+//!
+//! This works for any deterministic selection, which includes [`RandomPivot`] with a fixed seed.
+//!
+//! [`RandomPivot`]: crate::get_pivot::RandomPivot
+//!
+//! ```
+//! use alg_quickselect::{get_pivot::median_of_three, testing};
+//!
+//! let len = 1000;
+//! let killer = testing::adversary(len, len / 2, median_of_three);
+//! assert!(killer.comparisons > len * len / 8);
+//!
+//! // Replaying the input gives the same count
+//! assert_eq!(testing::comparisons(&killer.input, len / 2, median_of_three), killer.comparisons);
+//! ```
+
+use core::cmp::Ordering;
+use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+use not_empty::NonEmptySlice;
+
+use crate::{get_pivot::PivotStrategy, quickselect_with_strategy};
+
+/// Element compared through the adversary.
+///
+/// All the elements of an adversarial run share the state of the adversary,
+/// which decides the result of every comparison and counts them.
+#[derive(Debug, Clone)]
+pub struct Gas {
+    id: usize,
+    state: Rc<RefCell<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    /// Value of every element, [`State::gas`] for the elements that are still gas.
+    values: Vec<usize>,
+    /// Value for gas, greater than every frozen value.
+    gas: usize,
+    /// Next value to freeze an element to.
+    solid: usize,
+    /// Last gas element compared to a frozen one.
+    candidate: usize,
+    comparisons: usize,
+}
+
+impl State {
+    fn is_gas(&self, id: usize) -> bool {
+        self.values[id] == self.gas
+    }
+
+    fn freeze(&mut self, id: usize) {
+        self.values[id] = self.solid;
+        self.solid += 1;
+    }
+
+    fn compare(&mut self, x: usize, y: usize) -> Ordering {
+        self.comparisons += 1;
+        if self.is_gas(x) && self.is_gas(y) {
+            self.freeze(if x == self.candidate { x } else { y });
+        }
+        if self.is_gas(x) {
+            self.candidate = x;
+        } else if self.is_gas(y) {
+            self.candidate = y;
+        }
+        self.values[x].cmp(&self.values[y])
+    }
+}
+
+impl Ord for Gas {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.state.borrow_mut().compare(self.id, other.id)
+    }
+}
+
+impl PartialOrd for Gas {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Gas {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Gas {}
+
+/// Result of an adversarial run.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Adversary {
+    /// The worst-case input, a permutation of `0..len`.
+    pub input: Vec<usize>,
+    /// Number of comparisons made on it.
+    pub comparisons: usize,
+}
+
+/// Builds a worst-case input of length `len` for [`quickselect_with_strategy`] at rank `k` with `get_pivot`.
+///
+/// See the [module documentation](self).
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for `len`, or if `get_pivot` panics.
+pub fn adversary(len: usize, k: usize, get_pivot: impl PivotStrategy<Gas>) -> Adversary {
+    adversary_with(len, k, |s, k| {
+        quickselect_with_strategy(s, k, get_pivot);
+    })
+}
+
+/// [`adversary`] for any selection function, such as
+/// [`introselect_with`](crate::introselect_with) or
+/// [`quickselect_with_scheme`](crate::quickselect_with_scheme).
+///
+/// `select` is called once with the gas elements and `k`.
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for `len`, or if `select` panics.
+///
+/// # Examples
+///
+/// ```
+/// use alg_quickselect::{get_pivot::middle_index, introselect_with, testing};
+///
+/// let len = 1000;
+/// let killer = testing::adversary_with(len, len / 2, |s, k| {
+///     introselect_with(s, k, middle_index);
+/// });
+/// assert!(killer.comparisons < 100 * len);
+/// ```
+pub fn adversary_with(
+    len: usize,
+    k: usize,
+    select: impl FnOnce(&mut NonEmptySlice<Gas>, usize),
+) -> Adversary {
+    if k >= len {
+        panic!("index out of bounds: the len is {len} but the index is {k}");
+    }
+    let state = Rc::new(RefCell::new(State {
+        values: std::vec![len; len],
+        gas: len,
+        solid: 0,
+        candidate: 0,
+        comparisons: 0,
+    }));
+    run(&state, k, select);
+
+    let mut state = state.borrow_mut();
+    // The elements still gas were never compared to each other, any order works
+    for id in 0..len {
+        if state.is_gas(id) {
+            state.freeze(id);
+        }
+    }
+    Adversary {
+        input: state.values.clone(),
+        comparisons: state.comparisons,
+    }
+}
+
+/// Counts the comparisons made by [`quickselect_with_strategy`] at rank `k` with `get_pivot` on `input`.
+///
+/// # Panics
+///
+/// Panics if `k` is out of bounds for `input`, or if `get_pivot` panics.
+pub fn comparisons(input: &[usize], k: usize, get_pivot: impl PivotStrategy<Gas>) -> usize {
+    if k >= input.len() {
+        panic!(
+            "index out of bounds: the len is {len} but the index is {k}",
+            len = input.len()
+        );
+    }
+    let state = Rc::new(RefCell::new(State {
+        values: input.to_vec(),
+        // No element is gas
+        gas: usize::MAX,
+        solid: 0,
+        candidate: 0,
+        comparisons: 0,
+    }));
+    run(&state, k, |s, k| {
+        quickselect_with_strategy(s, k, get_pivot);
+    });
+    let comparisons = state.borrow().comparisons;
+    comparisons
+}
+
+/// Calls `select` with one element per value of `state`.
+fn run(state: &Rc<RefCell<State>>, k: usize, select: impl FnOnce(&mut NonEmptySlice<Gas>, usize)) {
+    let len = state.borrow().values.len();
+    let mut elements: Vec<_> = (0..len)
+        .map(|id| Gas {
+            id,
+            state: Rc::clone(state),
+        })
+        .collect();
+    // k < len, so there is at least one element
+    let s = NonEmptySlice::new_mut(&mut elements).unwrap();
+    select(s, k);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        get_pivot::{first_index, last_index, median_of_three, middle_index, ninther, RandomPivot},
+        introselect_with,
+    };
+
+    const LEN: usize = 1000;
+
+    /// Comparisons of the worst case for `2 * LEN` elements, divided by those for `LEN` elements,
+    /// both at the middle rank: about 4 for quadratic strategies, 2 for linear ones.
+    fn growth(mut killer: impl FnMut(usize) -> Adversary) -> usize {
+        let small = killer(LEN);
+        let large = killer(2 * LEN);
+        for killer in [&small, &large] {
+            let mut sorted = killer.input.clone();
+            sorted.sort_unstable();
+            assert!(sorted.iter().copied().eq(0..sorted.len()));
+        }
+        (large.comparisons + small.comparisons / 2) / small.comparisons
+    }
+
+    #[test]
+    fn quadratic_strategies() {
+        fn check(get_pivot: impl PivotStrategy<Gas> + Copy) {
+            assert_eq!(growth(|len| adversary(len, len / 2, get_pivot)), 4);
+
+            let killer = adversary(LEN, LEN - 1, get_pivot);
+            assert!(
+                killer.comparisons > LEN * LEN / 16,
+                "{}",
+                killer.comparisons
+            );
+            assert_eq!(
+                comparisons(&killer.input, LEN - 1, get_pivot),
+                killer.comparisons
+            );
+        }
+
+        check(first_index);
+        check(last_index);
+        check(middle_index);
+        check(median_of_three);
+        check(ninther);
+    }
+
+    #[test]
+    fn seeded_random() {
+        let killer = adversary(LEN, LEN / 2, RandomPivot::new(42));
+        assert!(killer.comparisons > LEN * LEN / 16);
+        let replay = comparisons(&killer.input, LEN / 2, RandomPivot::new(42));
+        assert_eq!(replay, killer.comparisons);
+    }
+
+    #[test]
+    fn introselect_is_linear() {
+        for get_pivot in [first_index, middle_index] {
+            let introselect = |len| {
+                adversary_with(len, len / 2, |s, k| {
+                    introselect_with(s, k, get_pivot);
+                })
+            };
+            assert_eq!(growth(introselect), 2);
+        }
+    }
+}
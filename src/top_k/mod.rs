@@ -0,0 +1,476 @@
+//! Extraction of the smallest or largest elements, and partial sorting.
+
+use core::{
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+use not_empty::NonEmptySlice;
+
+use crate::{get_pivot::Ninther, introselect_by, introselect_pivot, multiselect::select_ranks_by};
+
+/// Panics if `k` is greater than the length of `s`.
+#[inline]
+fn check_count<T>(s: &NonEmptySlice<T>, k: usize) {
+    if k > s.len().get() {
+        panic!(
+            "count out of bounds: the len is {len} but the count is {k}",
+            len = s.len().get(),
+        );
+    }
+}
+
+/// Returns the `k` smallest elements of an unsorted, non-empty slice, in no particular order.
+///
+/// The elements are moved to the front of `s`, and the returned subslice is that prefix.
+/// Runs in O(n) time on average, see [`introselect`](crate::introselect).
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::bottom_k;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let smallest = bottom_k(s, 2);
+/// smallest.sort();
+/// assert_eq!(smallest, [1, 2]);
+/// ```
+pub fn bottom_k<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut [T] {
+    bottom_k_by(s, k, T::cmp)
+}
+
+/// [`bottom_k`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`bottom_k`].
+pub fn bottom_k_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut [T] {
+    check_count(s, k);
+    if k > 0 && k < s.len().get() {
+        introselect_by(s, k - 1, compare);
+    }
+    &mut s[..k]
+}
+
+/// [`bottom_k`] with a key extraction function, see
+/// [`quickselect_by_key`](crate::quickselect_by_key).
+///
+/// # Panics
+///
+/// Same as [`bottom_k`].
+pub fn bottom_k_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    bottom_k_by(s, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Returns the `k` largest elements of an unsorted, non-empty slice, in no particular order.
+///
+/// The elements are moved to the back of `s`, and the returned subslice is that suffix.
+/// Runs in O(n) time on average, see [`introselect`](crate::introselect).
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::top_k;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// let largest = top_k(s, 2);
+/// largest.sort();
+/// assert_eq!(largest, [4, 5]);
+/// ```
+pub fn top_k<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut [T] {
+    top_k_by(s, k, T::cmp)
+}
+
+/// [`top_k`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// The largest elements are the last ones in the order defined by `compare`.
+///
+/// # Panics
+///
+/// Same as [`top_k`].
+pub fn top_k_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut [T] {
+    check_count(s, k);
+    let start = s.len().get() - k;
+    if k > 0 && start > 0 {
+        introselect_by(s, start, compare);
+    }
+    &mut s[start..]
+}
+
+/// [`top_k`] with a key extraction function, see
+/// [`quickselect_by_key`](crate::quickselect_by_key).
+///
+/// # Panics
+///
+/// Same as [`top_k`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::top_k_by_key;
+///
+/// let mut scores = [("ada", 41), ("bo", 67), ("cy", 23), ("di", 55)];
+/// let mut s = NonEmptySlice::new_mut(&mut scores).unwrap();
+///
+/// let best = top_k_by_key(s, 2, |&(_, score)| score);
+/// best.sort();
+/// assert_eq!(best, [("bo", 67), ("di", 55)]);
+/// ```
+pub fn top_k_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    top_k_by(s, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Returns the `k` smallest elements of an unsorted, non-empty slice, in ascending order.
+///
+/// Same as [`bottom_k`], but also sorts the returned prefix, and only that prefix.
+/// Runs in O(n + k log k) time on average.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::bottom_k_sorted;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(bottom_k_sorted(s, 3), [1, 2, 3]);
+/// ```
+pub fn bottom_k_sorted<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut [T] {
+    bottom_k_sorted_by(s, k, T::cmp)
+}
+
+/// [`bottom_k_sorted`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`bottom_k`].
+pub fn bottom_k_sorted_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut [T] {
+    let bottom = bottom_k_by(s, k, &mut compare);
+    bottom.sort_unstable_by(compare);
+    bottom
+}
+
+/// [`bottom_k_sorted`] with a key extraction function, see
+/// [`quickselect_by_key`](crate::quickselect_by_key).
+///
+/// # Panics
+///
+/// Same as [`bottom_k`].
+pub fn bottom_k_sorted_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    bottom_k_sorted_by(s, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Returns the `k` largest elements of an unsorted, non-empty slice, in descending order.
+///
+/// Same as [`top_k`], but also sorts the returned suffix, and only that suffix,
+/// so that the largest element comes first. Runs in O(n + k log k) time on average.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice `s`.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::top_k_sorted;
+///
+/// let mut arr = [4, 2, 5, 1, 3];
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// assert_eq!(top_k_sorted(s, 3), [5, 4, 3]);
+/// ```
+pub fn top_k_sorted<T: Ord>(s: &mut NonEmptySlice<T>, k: usize) -> &mut [T] {
+    top_k_sorted_by(s, k, T::cmp)
+}
+
+/// [`top_k_sorted`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// The returned suffix is sorted in the reverse of the order defined by `compare`.
+///
+/// # Panics
+///
+/// Same as [`top_k`].
+pub fn top_k_sorted_by<T>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut [T] {
+    let top = top_k_by(s, k, &mut compare);
+    top.sort_unstable_by(|a, b| compare(b, a));
+    top
+}
+
+/// [`top_k_sorted`] with a key extraction function, see
+/// [`quickselect_by_key`](crate::quickselect_by_key).
+///
+/// # Panics
+///
+/// Same as [`top_k`].
+pub fn top_k_sorted_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    k: usize,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    top_k_sorted_by(s, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Sorts the elements of an unsorted, non-empty slice that belong at the ranks in `range`,
+/// without sorting the rest of the slice.
+///
+/// After the call, `s[range]` holds the same elements as it would if the whole slice was sorted,
+/// and the returned subslice is `s[range]`. The elements before the range are less than or equal
+/// to them, and the elements after the range are greater than or equal to them. An empty range
+/// leaves the slice untouched.
+///
+/// The first and last ranks of the range are selected together with
+/// [`multiselect`](crate::multiselect), which only partitions further the parts that contain one
+/// of them, and then only the range is sorted. This runs in O(n + m log m) time, where `m` is the
+/// length of the range. The pivots are chosen like in [`introselect`](crate::introselect).
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds for the slice `s`, or if its start is greater than its end,
+/// like slice indexing.
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::sort_range;
+///
+/// let mut arr: [u32; 100] = core::array::from_fn(|i| (i as u32 * 37) % 100);
+/// let mut s = NonEmptySlice::new_mut(&mut arr).unwrap();
+///
+/// // The second page of 10 entries of a leaderboard
+/// assert_eq!(sort_range(s, 10..20), [10, 11, 12, 13, 14, 15, 16, 17, 18, 19]);
+/// ```
+pub fn sort_range<T: Ord>(s: &mut NonEmptySlice<T>, range: impl RangeBounds<usize>) -> &mut [T] {
+    sort_range_by(s, range, T::cmp)
+}
+
+/// [`sort_range`] with a comparator function, see [`quickselect_by`](crate::quickselect_by).
+///
+/// # Panics
+///
+/// Same as [`sort_range`].
+pub fn sort_range_by<T>(
+    s: &mut NonEmptySlice<T>,
+    range: impl RangeBounds<usize>,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> &mut [T] {
+    let len = s.len().get();
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice from after maximum usize")),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice up to maximum usize")),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end {
+        panic!("slice index starts at {start} but ends at {end}");
+    }
+    if end > len {
+        panic!("range end index {end} out of range for slice of length {len}");
+    }
+    if start == end {
+        return &mut s[start..end];
+    }
+
+    // start < end <= len, so both ranks are in bounds
+    let get_pivot = introselect_pivot(len, Ninther);
+    select_ranks_by(s, &[start, end - 1], &mut compare, get_pivot);
+
+    let range = &mut s[start..end];
+    range.sort_unstable_by(compare);
+    range
+}
+
+/// [`sort_range`] with a key extraction function, see
+/// [`quickselect_by_key`](crate::quickselect_by_key).
+///
+/// # Panics
+///
+/// Same as [`sort_range`].
+///
+/// # Examples
+///
+/// ```
+/// use not_empty::NonEmptySlice;
+/// use alg_quickselect::sort_range_by_key;
+///
+/// let mut scores = [("ada", 41), ("bo", 67), ("cy", 23), ("di", 55), ("ed", 12)];
+/// let mut s = NonEmptySlice::new_mut(&mut scores).unwrap();
+///
+/// let middle = sort_range_by_key(s, 1..4, |&(_, score)| score);
+/// assert_eq!(middle, [("cy", 23), ("ada", 41), ("di", 55)]);
+/// ```
+pub fn sort_range_by_key<T, K: Ord>(
+    s: &mut NonEmptySlice<T>,
+    range: impl RangeBounds<usize>,
+    mut f: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    sort_range_by(s, range, |a, b| f(a).cmp(&f(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::random;
+
+    #[test]
+    fn every_count() {
+        let data = random::<100>(1, 50);
+        let mut sorted = data;
+        sorted.sort_unstable();
+
+        for k in 0..=data.len() {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            assert_eq!(bottom_k_sorted(s, k), &sorted[..k]);
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let top = top_k_sorted(s, k);
+            top.reverse();
+            assert_eq!(top, &sorted[data.len() - k..]);
+
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let bottom = bottom_k(s, k);
+            bottom.sort_unstable();
+            assert_eq!(bottom, &sorted[..k]);
+        }
+    }
+
+    #[test]
+    fn by_key() {
+        let keys = random::<60>(2, 20);
+        let data: [(u32, usize); 60] = core::array::from_fn(|i| (keys[i], i));
+        let key = |&(x, _): &(u32, usize)| x;
+        let mut sorted = data.map(|(x, _)| x);
+        sorted.sort_unstable();
+
+        for k in 0..=data.len() {
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let bottom = bottom_k_sorted_by_key(s, k, key);
+            assert!(bottom.iter().map(key).eq(sorted[..k].iter().copied()));
+
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let top = top_k_sorted_by_key(s, k, key);
+            assert!(top
+                .iter()
+                .rev()
+                .map(key)
+                .eq(sorted[data.len() - k..].iter().copied()));
+
+            // The reverse order swaps the smallest and the largest elements
+            let mut arr = data;
+            let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+            let top = top_k_sorted_by(s, k, |a, b| key(b).cmp(&key(a)));
+            assert!(top.iter().map(key).eq(sorted[..k].iter().copied()));
+        }
+    }
+
+    #[test]
+    fn sort_every_range() {
+        let data = random::<40>(3, 25);
+        let mut sorted = data;
+        sorted.sort_unstable();
+
+        for start in 0..=data.len() {
+            for end in start..=data.len() {
+                let mut arr = data;
+                let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+                assert_eq!(sort_range(s, start..end), &sorted[start..end]);
+                if start == end {
+                    continue;
+                }
+                assert!(arr[..start]
+                    .iter()
+                    .all(|x| sorted[start..].iter().all(|y| x <= y)));
+                assert!(arr[end..]
+                    .iter()
+                    .all(|x| sorted[..end].iter().all(|y| x >= y)));
+            }
+        }
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(sort_range(s, ..=4), &sorted[..=4]);
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(sort_range(s, 35..), &sorted[35..]);
+
+        let mut arr = data;
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        let range = sort_range_by(s, 5..15, |a, b| b.cmp(a));
+        assert!(range.iter().eq(sorted[25..35].iter().rev()));
+        let s = NonEmptySlice::new_mut(&mut arr).unwrap();
+        assert_eq!(sort_range_by_key(s, 20..30, |&x| x), &sorted[20..30]);
+    }
+
+    #[test]
+    #[should_panic = "out of range"]
+    fn range_too_long() {
+        let mut arr = [1, 2, 3];
+        sort_range(NonEmptySlice::new_mut(&mut arr).unwrap(), 1..4);
+    }
+
+    #[test]
+    #[should_panic = "count out of bounds"]
+    fn too_many() {
+        let mut arr = [1, 2, 3];
+        top_k(NonEmptySlice::new_mut(&mut arr).unwrap(), 4);
+    }
+}